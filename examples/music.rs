@@ -9,7 +9,6 @@ use rust_music_theory::{
 use std::{
     sync::{Arc, Mutex},
     thread,
-    time::Duration,
 };
 
 // Audio quality
@@ -78,11 +77,10 @@ impl Audio {
         }
     }
 
-    /// Play samples.
-    pub fn play(&mut self, samples: Vec<usfx::Sample>) {
-        let mut mixer = self.mixer.lock().unwrap();
-        // Add all the samples to the mixer
-        samples.into_iter().for_each(|sample| mixer.play(sample));
+    /// Play a sequence of patterns.
+    pub fn play(&mut self, sequencer: usfx::Sequencer) {
+        // The mixer starts the samples of the sequencer at the exact frame they should play
+        self.mixer.lock().unwrap().load_sequencer(sequencer);
     }
 
     /// Start a thread which will emit the audio.
@@ -91,36 +89,33 @@ impl Audio {
     }
 }
 
-fn kick(rng: &mut ThreadRng) -> Vec<usfx::Sample> {
+fn kick(rng: &mut ThreadRng) -> usfx::Sample {
     // Combine a short high punch with a longer low bass
-    vec![*usfx::Sample::default()
+    *usfx::Sample::default()
         .volume(0.5)
         .osc_frequency(150)
         .osc_type(usfx::OscillatorType::Triangle)
         .env_attack(0.07)
         .env_decay(0.05)
         .env_sustain(0.9)
-        .env_release(rng.gen_range(0.1..0.2))]
+        .env_release(rng.gen_range(0.1..0.2))
 }
 
-fn hat() -> Vec<usfx::Sample> {
+fn hat() -> usfx::Sample {
     // An annoying high chirpy sound
-    vec![*usfx::Sample::default()
+    *usfx::Sample::default()
         .volume(0.2)
         .osc_type(usfx::OscillatorType::Noise)
         .env_attack(0.02)
         .env_decay(0.02)
         .env_sustain(0.7)
-        .env_release(0.0)]
+        .env_release(0.0)
 }
 
-fn lead(lead_frequencies: &[usize], index: &mut usize) -> Vec<usfx::Sample> {
-    *index = (*index + 1) % lead_frequencies.len();
-
-    // The lead synth, frequency is based on the generated scale
-    vec![*usfx::Sample::default()
+fn lead() -> usfx::Sample {
+    // The lead synth, the note is set by the sequencer
+    *usfx::Sample::default()
         .volume(0.5)
-        .osc_frequency(lead_frequencies[*index])
         .osc_type(usfx::OscillatorType::Square)
         .osc_duty_cycle(usfx::DutyCycle::Eight)
         .env_attack(0.02)
@@ -128,10 +123,10 @@ fn lead(lead_frequencies: &[usize], index: &mut usize) -> Vec<usfx::Sample> {
         .env_sustain(0.4)
         .env_release(0.5)
         .dis_crunch(0.3)
-        .dis_drive(0.2)]
+        .dis_drive(0.2)
 }
 
-fn generate_lead_frequencies(mut rng: &mut ThreadRng) -> Vec<usize> {
+fn generate_lead_notes(mut rng: &mut ThreadRng) -> Vec<usfx::Note> {
     // Generate a scale for the lead
     let scale = Scale::new(
         ScaleType::HarmonicMinor,
@@ -145,25 +140,15 @@ fn generate_lead_frequencies(mut rng: &mut ThreadRng) -> Vec<usize> {
     // Get the notes
     let scale_notes = scale.notes();
 
-    // Choose 8 random notes
+    // Choose 8 random notes, the names of the scale notes such as "C#4" can be parsed directly
     (0..8)
-        .map(
-            |_| match scale_notes.iter().choose(&mut rng).unwrap().pitch_class {
-                // Convert the pitch class of the note to a frequency
-                PitchClass::C => 262,
-                PitchClass::Cs => 277,
-                PitchClass::D => 294,
-                PitchClass::Ds => 311,
-                PitchClass::E => 330,
-                PitchClass::F => 349,
-                PitchClass::Fs => 370,
-                PitchClass::G => 392,
-                PitchClass::Gs => 415,
-                PitchClass::A => 440,
-                PitchClass::As => 466,
-                PitchClass::B => 494,
-            },
-        )
+        .map(|_| {
+            let note = scale_notes.iter().choose(&mut rng).unwrap();
+
+            format!("{}{}", note.pitch_class, note.octave)
+                .parse()
+                .unwrap()
+        })
         .collect()
 }
 
@@ -172,37 +157,34 @@ fn main() {
     let mut audio = Audio::new();
     audio.run();
 
-    // The delay needed to follow the BPM
-    let beat_delay_milliseconds = (60.0 / BPM * 1000.0 / 4.0) as u64;
-
     // Initialize the random number generator
     let mut rng = thread_rng();
 
-    // Procedurally generate frequencies for the lead
-    let lead_frequencies = generate_lead_frequencies(&mut rng);
-
-    let mut current_lead = 0;
-
-    // Really ugly way to layout a track
-    loop {
-        // If we want the music to play at the exact same time it's better to chain the vectors,
-        // but having a "random" delay creates a more organic feeling
-        audio.play(kick(&mut rng));
-        audio.play(hat());
-
-        thread::sleep(Duration::from_millis(beat_delay_milliseconds));
+    // Procedurally generate notes for the lead
+    let lead_notes = generate_lead_notes(&mut rng);
 
-        audio.play(hat());
+    // Layout a track of sixteenth notes, every beat has a kick and a lead note
+    let mut pattern = usfx::Pattern::new(lead_notes.len() * 4);
+    for (beat, note) in lead_notes.into_iter().enumerate() {
+        let step = beat * 4;
 
-        thread::sleep(Duration::from_millis(beat_delay_milliseconds));
+        pattern.trigger(step, kick(&mut rng), None, None);
+        pattern.trigger(step + 2, lead(), Some(note), Some(1.0));
 
-        audio.play(lead(&lead_frequencies[..], &mut current_lead));
-        audio.play(hat());
+        // A hat on every step
+        for hat_step in step..step + 4 {
+            pattern.trigger(hat_step, hat(), None, None);
+        }
+    }
 
-        thread::sleep(Duration::from_millis(beat_delay_milliseconds));
+    // A bit of swing creates a more organic feeling
+    let mut sequencer = usfx::Sequencer::new(BPM);
+    sequencer.swing(0.1).pattern(pattern);
 
-        audio.play(hat());
+    audio.play(sequencer);
 
-        thread::sleep(Duration::from_millis(beat_delay_milliseconds));
+    // The sequencer keeps looping in the audio thread
+    loop {
+        thread::park();
     }
 }
@@ -0,0 +1,36 @@
+/// Change the pitch once after a set time.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Arpeggio {
+    /// Multiplier of the frequency after the change.
+    multiplier: f32,
+    /// Offset at which the pitch changes.
+    change_offset: usize,
+}
+
+impl Arpeggio {
+    /// Instantiate a new pitch change after the time in seconds.
+    pub fn new(sample_rate: f32, multiplier: f32, time: f32) -> Self {
+        Self {
+            multiplier,
+            change_offset: (time.max(0.0) * sample_rate) as usize,
+        }
+    }
+
+    /// Multiplier of the frequency at the offset.
+    pub fn rate(&self, offset: usize) -> f32 {
+        if offset >= self.change_offset {
+            self.multiplier
+        } else {
+            1.0
+        }
+    }
+
+    /// Amount of frames from the offset until the pitch changes, if it didn't change yet.
+    pub fn frames_until_change(&self, offset: usize) -> Option<usize> {
+        if offset < self.change_offset {
+            Some(self.change_offset - offset)
+        } else {
+            None
+        }
+    }
+}
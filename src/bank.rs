@@ -0,0 +1,159 @@
+use crate::{random::Random, Sample};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// How deep groups and pools can reference each other, prevents infinite loops.
+const MAX_DEPTH: usize = 8;
+
+/// A collection of named samples that can be played with [`Mixer::play_named`].
+///
+/// Besides samples it can contain groups, which play all their members at the same time, and
+/// pools, which play a single randomly picked member.
+/// Members of groups and pools can be the names of samples, groups or other pools.
+///
+/// With the `serde` feature enabled the bank can be loaded from any format supported by serde,
+/// such as RON, TOML or JSON:
+///
+/// ```ron
+/// (
+///     volume: Some(0.8),
+///     samples: {
+///         "explosion_small": (version: 1, osc_type: Noise, env_release: 0.3),
+///         "explosion_big": (version: 1, osc_type: Noise, env_release: 0.8),
+///     },
+///     groups: {},
+///     pools: {
+///         "explosion": ["explosion_small", "explosion_big"],
+///     },
+/// )
+/// ```
+///
+/// ```rust
+/// let mut bank = usfx::SoundBank::default();
+/// bank.insert("coin", usfx::Sample::preset_pickup(0));
+/// bank.insert("coin_alt", usfx::Sample::preset_pickup(1));
+/// bank.insert_pool("pickup", &["coin", "coin_alt"]);
+///
+/// let mut mixer = usfx::Mixer::default();
+/// mixer.load_bank(bank);
+///
+/// // Play one of the two coin sounds
+/// assert!(mixer.play_named("pickup"));
+/// ```
+///
+/// [`Mixer::play_named`]: struct.Mixer.html#method.play_named
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[derive(Debug, Clone, Default)]
+pub struct SoundBank {
+    /// Volume of the mixer when the bank is loaded.
+    volume: Option<f32>,
+    /// Seed of the random number generator of the mixer when the bank is loaded.
+    seed: Option<u64>,
+    /// The samples with their names.
+    samples: HashMap<String, Sample>,
+    /// Names of members that are played together.
+    groups: HashMap<String, Vec<String>>,
+    /// Names of members from which a random one is played.
+    pools: HashMap<String, Vec<String>>,
+}
+
+impl SoundBank {
+    /// Set the volume of the mixer the bank is loaded in.
+    ///
+    /// A range from 0.0-1.0 will result in proper behavior, but you can experiment with other
+    /// values.
+    pub fn volume(&mut self, volume: f32) -> &mut Self {
+        self.volume = Some(volume);
+
+        self
+    }
+
+    /// Set the seed of the random number generator of the mixer the bank is loaded in.
+    pub fn seed(&mut self, seed: u64) -> &mut Self {
+        self.seed = Some(seed);
+
+        self
+    }
+
+    /// Add a sample with a name, an existing sample with the same name is replaced.
+    pub fn insert(&mut self, name: &str, sample: Sample) -> &mut Self {
+        self.samples.insert(name.to_string(), sample);
+
+        self
+    }
+
+    /// Add a group of members that are all played at the same time when the name is played.
+    pub fn insert_group(&mut self, name: &str, members: &[&str]) -> &mut Self {
+        self.groups.insert(
+            name.to_string(),
+            members.iter().map(|member| member.to_string()).collect(),
+        );
+
+        self
+    }
+
+    /// Add a pool of members from which a random one is played when the name is played.
+    pub fn insert_pool(&mut self, name: &str, members: &[&str]) -> &mut Self {
+        self.pools.insert(
+            name.to_string(),
+            members.iter().map(|member| member.to_string()).collect(),
+        );
+
+        self
+    }
+
+    /// Get a sample by name, groups and pools are not included.
+    pub fn get(&self, name: &str) -> Option<&Sample> {
+        self.samples.get(name)
+    }
+
+    /// Whether there is a sample, group or pool with the name.
+    pub fn contains(&self, name: &str) -> bool {
+        self.samples.contains_key(name)
+            || self.groups.contains_key(name)
+            || self.pools.contains_key(name)
+    }
+
+    /// The volume the mixer should use.
+    pub(crate) fn mixer_volume(&self) -> Option<f32> {
+        self.volume
+    }
+
+    /// The seed the mixer should use.
+    pub(crate) fn mixer_seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /// Find all samples that should be played for the name.
+    pub(crate) fn resolve(&self, name: &str, random: &mut Random, samples: &mut Vec<Sample>) {
+        self.resolve_with_depth(name, random, samples, 0);
+    }
+
+    /// Find all samples recursively.
+    fn resolve_with_depth(
+        &self,
+        name: &str,
+        random: &mut Random,
+        samples: &mut Vec<Sample>,
+        depth: usize,
+    ) {
+        if depth > MAX_DEPTH {
+            return;
+        }
+
+        if let Some(sample) = self.samples.get(name) {
+            samples.push(*sample);
+        } else if let Some(group) = self.groups.get(name) {
+            group
+                .iter()
+                .for_each(|member| self.resolve_with_depth(member, random, samples, depth + 1));
+        } else if let Some(pool) = self.pools.get(name) {
+            if !pool.is_empty() {
+                let member = &pool[random.index(pool.len())];
+                self.resolve_with_depth(member, random, samples, depth + 1);
+            }
+        }
+    }
+}
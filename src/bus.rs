@@ -0,0 +1,301 @@
+use crate::{Effect, Mixer};
+
+/// Identifier of a bus added to a [`Mixer`].
+///
+/// [`Mixer`]: struct.Mixer.html
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct BusId {
+    /// The mixer the bus is added to.
+    pub(crate) mixer: usize,
+    /// Index of the bus in the mixer.
+    pub(crate) index: usize,
+}
+
+/// A group of voices with it's own volume and effects, the result goes into the parent bus or
+/// the output of the mixer.
+#[derive(Debug)]
+pub(crate) struct Bus {
+    /// Name to find the bus.
+    pub(crate) name: String,
+    /// The bus the result goes into, the output of the mixer when not set.
+    pub(crate) parent: Option<BusId>,
+    /// Multiplier of the result.
+    pub(crate) volume: f32,
+    /// Whether the result is silenced.
+    pub(crate) muted: bool,
+    /// Whether only soloed buses are heard.
+    pub(crate) solo: bool,
+    /// Whether the voices are frozen.
+    pub(crate) paused: bool,
+    /// Effects applied in order on the combined voices.
+    pub(crate) effects: Vec<Box<dyn Effect>>,
+    /// Whether the voices are heard, which is not the case when another bus is soloed.
+    pub(crate) audible: bool,
+    /// Whether the voices run, which is not the case when the bus or a parent is paused.
+    pub(crate) running: bool,
+    /// The combined voices and child buses.
+    pub(crate) buffer: Vec<f32>,
+    /// The total offset.
+    pub(crate) offset: usize,
+}
+
+impl Mixer {
+    /// Add a bus that voices can be played on, such as "sfx", "music" or "ambience".
+    ///
+    /// The result of the bus goes into the parent bus, or into the output of the mixer when there's
+    /// no parent.
+    /// Every bus has it's own volume, mute, solo and effects.
+    ///
+    /// ```rust
+    /// let mut mixer = usfx::Mixer::default();
+    /// let music = mixer.add_bus("music", None);
+    /// let sfx = mixer.add_bus("sfx", None);
+    /// let explosions = mixer.add_bus("explosions", Some(sfx));
+    ///
+    /// // Settings from the options menu
+    /// mixer.bus_volume(music, 0.6);
+    /// mixer.bus_volume(sfx, 0.8);
+    ///
+    /// // Make the explosions crunchy
+    /// mixer.add_bus_effect(explosions, usfx::Distortion::new(0.6, 0.9));
+    ///
+    /// mixer.play_on_bus(usfx::Sample::preset_explosion(0), explosions);
+    /// # let mut buffer = [0.0; 1024];
+    /// mixer.generate(&mut buffer);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// When the parent is not a bus of this mixer.
+    pub fn add_bus(&mut self, name: &str, parent: Option<BusId>) -> BusId {
+        if let Some(parent) = parent {
+            assert!(self.contains_bus(parent), "parent bus doesn't exist");
+        }
+
+        self.buses.push(Bus {
+            name: name.to_string(),
+            parent,
+            volume: 1.0,
+            muted: false,
+            solo: false,
+            paused: false,
+            effects: vec![],
+            audible: false,
+            running: true,
+            buffer: vec![],
+            offset: 0,
+        });
+
+        BusId {
+            mixer: self.id,
+            index: self.buses.len() - 1,
+        }
+    }
+
+    /// Find a bus by name.
+    pub fn bus(&self, name: &str) -> Option<BusId> {
+        self.buses
+            .iter()
+            .position(|bus| bus.name == name)
+            .map(|index| BusId {
+                mixer: self.id,
+                index,
+            })
+    }
+
+    /// Set the volume of a bus, it's a multiplier of the combined voices and child buses.
+    ///
+    /// A range from 0.0-1.0 will result in proper behavior, but you can experiment with other
+    /// values.
+    pub fn bus_volume(&mut self, bus: BusId, volume: f32) {
+        if let Some(bus) = self.bus_mut(bus) {
+            bus.volume = volume;
+        }
+    }
+
+    /// Silence or unsilence a bus and all of it's child buses.
+    pub fn mute_bus(&mut self, bus: BusId, muted: bool) {
+        if let Some(bus) = self.bus_mut(bus) {
+            bus.muted = muted;
+        }
+    }
+
+    /// Solo a bus, when any bus is soloed only the voices of soloed buses and their child buses
+    /// are heard.
+    pub fn solo_bus(&mut self, bus: BusId, solo: bool) {
+        if let Some(bus) = self.bus_mut(bus) {
+            bus.solo = solo;
+        }
+    }
+
+    /// Pause or resume all voices of a bus and all of it's child buses.
+    ///
+    /// Paused voices stay where they are and continue when the bus is resumed, such as the sound
+    /// effects when the game is paused while the user interface sounds keep playing.
+    /// Voices played on a paused bus start when it's resumed, sequencers, songs and instruments
+    /// playing on the bus are paused as well.
+    pub fn pause_bus(&mut self, bus: BusId, paused: bool) {
+        if let Some(bus) = self.bus_mut(bus) {
+            bus.paused = paused;
+        }
+    }
+
+    /// Add an effect to the end of the effect chain of a bus.
+    pub fn add_bus_effect<E>(&mut self, bus: BusId, effect: E)
+    where
+        E: Effect + 'static,
+    {
+        if let Some(bus) = self.bus_mut(bus) {
+            bus.effects.push(Box::new(effect));
+        }
+    }
+
+    /// Mix the buses into their parents and the output, the children and the buses ducking them
+    /// are mixed first.
+    pub(crate) fn mix_buses(&mut self, output: &mut [f32]) {
+        // Reuse the buffer so nothing is allocated while generating
+        let mut mixed = std::mem::take(&mut self.mixed_buses);
+        mixed.clear();
+        mixed.resize(self.buses.len(), false);
+        while let Some(index) = self.next_bus(&mixed) {
+            mixed[index] = true;
+
+            let mut buffer = std::mem::take(&mut self.buses[index].buffer);
+
+            let bus = &mut self.buses[index];
+            let offset = bus.offset;
+            bus.effects
+                .iter_mut()
+                .for_each(|effect| effect.apply(&mut buffer, offset));
+            bus.offset += buffer.len();
+
+            let volume = if bus.muted { 0.0 } else { bus.volume };
+            buffer.iter_mut().for_each(|tone| *tone *= volume);
+
+            // Lower the volume while other buses are loud and measure this one for the buses it
+            // ducks
+            for ducker in self.duckers.iter_mut() {
+                if ducker.target.index == index {
+                    buffer
+                        .iter_mut()
+                        .zip(ducker.gains.iter())
+                        .for_each(|(tone, gain)| *tone *= *gain);
+                }
+            }
+            for ducker in self.duckers.iter_mut() {
+                if ducker.source.index == index {
+                    ducker.measure(&buffer, self.sample_rate);
+                }
+            }
+
+            let target = match self.buses[index].parent {
+                Some(parent) => &mut self.buses[parent.index].buffer[..],
+                None => &mut output[..],
+            };
+            target
+                .iter_mut()
+                .zip(buffer.iter())
+                .for_each(|(tone, mixed)| *tone += *mixed);
+
+            self.buses[index].buffer = buffer;
+        }
+
+        self.mixed_buses = mixed;
+    }
+
+    /// Find the next bus that can be mixed.
+    fn next_bus(&self, mixed: &[bool]) -> Option<usize> {
+        let ready = |index: usize| {
+            let children_mixed = self.buses.iter().enumerate().all(|(child, bus)| {
+                bus.parent.map(|parent| parent.index) != Some(index) || mixed[child]
+            });
+            let sources_mixed = self
+                .duckers
+                .iter()
+                .all(|ducker| ducker.target.index != index || mixed[ducker.source.index]);
+
+            !mixed[index] && children_mixed && sources_mixed
+        };
+
+        // When buses duck each other the last one is mixed with the ducking of the previous
+        // buffer, a child is always added after it's parent so it has no children left
+        (0..self.buses.len())
+            .rev()
+            .find(|index| ready(*index))
+            .or_else(|| (0..self.buses.len()).rev().find(|index| !mixed[*index]))
+    }
+
+    /// Whether the bus is added to this mixer.
+    pub(crate) fn contains_bus(&self, bus: BusId) -> bool {
+        bus.mixer == self.id && bus.index < self.buses.len()
+    }
+
+    /// Get a bus when it's added to this mixer.
+    fn bus_mut(&mut self, bus: BusId) -> Option<&mut Bus> {
+        if self.contains_bus(bus) {
+            self.buses.get_mut(bus.index)
+        } else {
+            None
+        }
+    }
+
+    /// Clear the buffers of the buses and find out which are soloed and paused.
+    pub(crate) fn prepare_buses(&mut self, frames: usize) {
+        let any_solo = self.buses.iter().any(|bus| bus.solo);
+
+        // A parent is always added before it's child
+        for index in 0..self.buses.len() {
+            let parent = self.buses[index]
+                .parent
+                .map(|parent| &self.buses[parent.index]);
+            let parent_audible = parent.is_some_and(|parent| parent.audible);
+            let parent_running = parent.is_none_or(|parent| parent.running);
+
+            let bus = &mut self.buses[index];
+            bus.audible = !any_solo || bus.solo || parent_audible;
+            bus.running = !bus.paused && parent_running;
+            bus.buffer.clear();
+            bus.buffer.resize(frames, 0.0);
+        }
+
+        self.duckers
+            .iter_mut()
+            .for_each(|ducker| ducker.prepare(frames));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BusId;
+    use crate::{Ducking, Mixer, Pattern, Sample, Sequencer};
+
+    #[test]
+    fn buses_of_other_mixers_are_ignored() {
+        let mut other = Mixer::default();
+        other.add_bus("first", None);
+        let foreign = other.add_bus("second", None);
+
+        let mut mixer = Mixer::default();
+        let bus = mixer.add_bus("only", None);
+        let past_end = BusId {
+            mixer: bus.mixer,
+            index: 10,
+        };
+
+        for bus in [foreign, past_end] {
+            mixer.play_on_bus(Sample::default(), bus);
+            mixer.duck(bus, bus, Ducking::default());
+            mixer.bus_volume(bus, 0.5);
+
+            let mut pattern = Pattern::new(1);
+            pattern.trigger(0, Sample::default(), None, None);
+            let mut sequencer = Sequencer::new(120.0);
+            sequencer.pattern(pattern).bus(bus);
+            mixer.load_sequencer(sequencer);
+
+            let mut buffer = [0.0; 1024];
+            mixer.generate(&mut buffer);
+            assert!(buffer.iter().any(|tone| *tone != 0.0));
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use crate::Mixer;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The kind of sound a sample is, every category has it's own volume in the [`Mixer`].
+///
+/// ```rust
+/// let mut click = usfx::Sample::preset_blip(0);
+/// click.category(usfx::Category::Ui);
+///
+/// let mut mixer = usfx::Mixer::default();
+/// // Turn down the sound effects but keep the interface at full volume
+/// mixer.category_volume(usfx::Category::Sfx, 0.5);
+/// mixer.play(click);
+/// ```
+///
+/// [`Mixer`]: struct.Mixer.html
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum Category {
+    /// Sounds of the user interface.
+    Ui,
+    /// Sound effects of the game.
+    #[default]
+    Sfx,
+    /// Music.
+    Music,
+    /// Spoken lines and other vocal sounds.
+    Voice,
+}
+
+impl Mixer {
+    /// Set the volume of all samples of a category, including the ones that are playing.
+    ///
+    /// A range from 0.0-1.0 will result in proper behavior, but you can experiment with other
+    /// values.
+    pub fn category_volume(&mut self, category: Category, volume: f32) {
+        self.category_volumes.insert(category, volume);
+    }
+
+    /// Silence or unsilence all samples of a category, they keep playing while muted.
+    pub fn mute_category(&mut self, category: Category, muted: bool) {
+        if muted {
+            self.muted_categories.insert(category);
+        } else {
+            self.muted_categories.remove(&category);
+        }
+    }
+}
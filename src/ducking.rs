@@ -0,0 +1,156 @@
+use crate::{BusId, Mixer};
+
+/// Time in seconds the measured level of a bus falls from full volume to silence, so the
+/// ducking doesn't flutter on every zero crossing of the waveform.
+const LEVEL_FALL_TIME: f32 = 0.05;
+
+/// How a bus lowers the volume of another bus while it's loud, like a sidechain compressor.
+///
+/// ```rust
+/// let mut mixer = usfx::Mixer::default();
+/// let music = mixer.add_bus("music", None);
+/// let dialog = mixer.add_bus("dialog", None);
+///
+/// // Halve the volume of the music while someone is talking
+/// let mut ducking = usfx::Ducking::default();
+/// ducking.threshold(0.05).amount(0.5).attack(0.1).release(0.8);
+/// mixer.duck(dialog, music, ducking);
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct Ducking {
+    threshold: f32,
+    amount: f32,
+    attack: f32,
+    release: f32,
+}
+
+impl Default for Ducking {
+    fn default() -> Self {
+        Self {
+            threshold: 0.05,
+            amount: 0.5,
+            attack: 0.05,
+            release: 0.5,
+        }
+    }
+}
+
+impl Ducking {
+    /// Set the level the bus must reach before the other bus is ducked.
+    ///
+    /// A range from 0.0-1.0 will result in proper behavior.
+    pub fn threshold(&mut self, threshold: f32) -> &mut Self {
+        self.threshold = threshold;
+
+        self
+    }
+
+    /// Set how much the volume of the other bus is lowered, 1.0 silences it completely.
+    ///
+    /// A range from 0.0-1.0 will result in proper behavior.
+    pub fn amount(&mut self, amount: f32) -> &mut Self {
+        self.amount = amount;
+
+        self
+    }
+
+    /// Set the time in seconds it takes to lower the volume.
+    pub fn attack(&mut self, attack: f32) -> &mut Self {
+        self.attack = attack;
+
+        self
+    }
+
+    /// Set the time in seconds it takes to restore the volume after the level drops below the
+    /// threshold.
+    pub fn release(&mut self, release: f32) -> &mut Self {
+        self.release = release;
+
+        self
+    }
+}
+
+/// A bus lowering the volume of another bus.
+#[derive(Debug, Clone)]
+pub(crate) struct Ducker {
+    /// The bus which level is measured.
+    pub(crate) source: BusId,
+    /// The bus which volume is lowered.
+    pub(crate) target: BusId,
+    /// The settings.
+    ducking: Ducking,
+    /// The measured level of the source.
+    level: f32,
+    /// The current multiplier of the target.
+    gain: f32,
+    /// The multiplier of the target for every frame of the buffer.
+    pub(crate) gains: Vec<f32>,
+}
+
+impl Ducker {
+    /// Calculate the multipliers of the target from the result of the source.
+    pub(crate) fn measure(&mut self, buffer: &[f32], sample_rate: usize) {
+        let sample_rate = sample_rate as f32;
+        let fall = 1.0 / (LEVEL_FALL_TIME * sample_rate);
+        let ducked = 1.0 - self.ducking.amount.clamp(0.0, 1.0);
+        let attack = (1.0 - ducked) / (self.ducking.attack * sample_rate).max(1.0);
+        let release = (1.0 - ducked) / (self.ducking.release * sample_rate).max(1.0);
+
+        self.gains.clear();
+        for tone in buffer.iter() {
+            self.level = tone.abs().max(self.level - fall);
+
+            self.gain = if self.level >= self.ducking.threshold {
+                (self.gain - attack).max(ducked)
+            } else {
+                (self.gain + release).min(1.0)
+            };
+            self.gains.push(self.gain);
+        }
+    }
+
+    /// Keep the current multiplier for the next buffer until it's measured.
+    pub(crate) fn prepare(&mut self, frames: usize) {
+        self.gains.clear();
+        self.gains.resize(frames, self.gain);
+    }
+}
+
+impl Mixer {
+    /// Lower the volume of the target bus while the source bus is loud, see [`Ducking`].
+    ///
+    /// The level is measured after the effects and the volume of the source, so a muted bus
+    /// doesn't duck anything.
+    /// To let a single voice duck a bus play it on it's own bus.
+    /// Ducking the same buses again replaces the previous settings, buses that are not added to
+    /// this mixer are ignored.
+    ///
+    /// [`Ducking`]: struct.Ducking.html
+    pub fn duck(&mut self, source: BusId, target: BusId, ducking: Ducking) {
+        if !self.contains_bus(source) || !self.contains_bus(target) {
+            return;
+        }
+
+        match self
+            .duckers
+            .iter_mut()
+            .find(|ducker| ducker.source == source && ducker.target == target)
+        {
+            Some(ducker) => ducker.ducking = ducking,
+            None => self.duckers.push(Ducker {
+                source,
+                target,
+                ducking,
+                level: 0.0,
+                gain: 1.0,
+                gains: vec![],
+            }),
+        }
+    }
+
+    /// Stop the source bus from lowering the volume of the target bus.
+    pub fn stop_ducking(&mut self, source: BusId, target: BusId) {
+        self.duckers
+            .retain(|ducker| ducker.source != source || ducker.target != target);
+    }
+}
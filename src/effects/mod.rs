@@ -1,4 +1,5 @@
 pub mod distortion;
+pub mod phaser;
 
 use std::fmt::Debug;
 
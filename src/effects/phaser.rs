@@ -0,0 +1,74 @@
+use crate::effects::Effect;
+
+/// The sample rate sfxr uses to calculate it's parameters.
+const SFXR_SAMPLE_RATE: f32 = 44_100.0;
+/// The amount of times sfxr supersamples, the delay is calculated in supersamples.
+const SFXR_SUPERSAMPLING: f32 = 8.0;
+/// The maximum delay sfxr allows in supersamples.
+const SFXR_MAX_DELAY: f32 = 1023.0;
+
+/// A comb filter with a sliding delay, the same as the phaser of sfxr.
+#[derive(Debug)]
+pub struct Phaser {
+    /// The current delay in sfxr supersamples.
+    delay: f32,
+    /// How much the delay changes every frame in sfxr supersamples.
+    sweep: f32,
+    /// Convert the delay in sfxr supersamples to the frames of our sample rate.
+    delay_to_frames: f32,
+    /// Ring buffer of the previous values.
+    history: Vec<f32>,
+    /// The position in the ring buffer that will be written next.
+    position: usize,
+}
+
+impl Phaser {
+    /// Setup the effect with the parameters of sfxr.
+    pub fn new(offset: f32, sweep: f32, sample_rate: usize) -> Self {
+        let sample_rate = sample_rate as f32;
+
+        // The parameters are squared while keeping the sign, like sfxr does
+        let delay = offset * offset.abs() * 1020.0;
+        let sweep = sweep * sweep.abs() * SFXR_SAMPLE_RATE / sample_rate;
+
+        let delay_to_frames = sample_rate / SFXR_SAMPLE_RATE / SFXR_SUPERSAMPLING;
+
+        // Make the history big enough to hold the biggest delay and the frame after it
+        let history_size = (SFXR_MAX_DELAY * delay_to_frames).ceil() as usize + 2;
+
+        Self {
+            delay,
+            sweep,
+            delay_to_frames,
+            history: vec![0.0; history_size],
+            position: 0,
+        }
+    }
+}
+
+impl Effect for Phaser {
+    /// Apply the effect on the buffer.
+    ///
+    /// Algorithm from: https://github.com/grimfang4/sfxr
+    fn apply(&mut self, buffer: &mut [f32], _offset: usize) {
+        let history_len = self.history.len();
+
+        buffer.iter_mut().for_each(|tone| {
+            self.delay += self.sweep;
+            let delay = self.delay.abs().min(SFXR_MAX_DELAY) * self.delay_to_frames;
+
+            self.history[self.position] = *tone;
+
+            // Interpolate between the two frames surrounding the delay since sfxr uses a higher
+            // sample rate for the delay
+            let frames = delay as usize;
+            let fract = delay.fract();
+            let first = self.history[(self.position + history_len - frames) % history_len];
+            let second = self.history[(self.position + history_len - frames - 1) % history_len];
+
+            *tone += first + (second - first) * fract;
+
+            self.position = (self.position + 1) % history_len;
+        });
+    }
+}
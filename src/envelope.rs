@@ -1,8 +1,69 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Time in seconds the level glides from the end of the loop region to it's start, so going back
+/// doesn't click.
+const LOOP_GLIDE_TIME: f32 = 0.005;
+
+/// How often the loop region of the envelope of a sample is repeated.
+///
+/// The loop region is a part of the envelope, when it's end is reached the envelope goes back to
+/// the state it had at the start of the region.
+/// A region over the attack and the decay plays them again as a pulse, a region within the
+/// sustain holds it longer.
+/// While there are repeats left the envelope holds it's sustain height instead of releasing after
+/// the decay, after the last repeat it plays on and releases as usual.
+///
+/// ```rust
+/// // An engine that runs until it's released
+/// let mut engine = usfx::Sample::default();
+/// engine
+///     .osc_type(usfx::OscillatorType::Saw)
+///     .osc_frequency(80)
+///     .env_attack(0.2)
+///     .env_release(0.3)
+///     .looping(usfx::Looping::Infinite);
+///
+/// // An alarm that beeps 5 times by repeating it's attack and decay
+/// let mut alarm = usfx::Sample::default();
+/// alarm
+///     .osc_type(usfx::OscillatorType::Square)
+///     .env_attack(0.01)
+///     .env_decay(0.15)
+///     .env_sustain(0.1)
+///     .looping(usfx::Looping::Times(4))
+///     .loop_start(0.0)
+///     .loop_end(0.2);
+///
+/// let mut mixer = usfx::Mixer::default();
+/// let voice = mixer.play(engine);
+/// mixer.play(alarm);
+/// # let mut buffer = [0.0; 1024];
+/// # mixer.generate(&mut buffer);
+///
+/// // Turn off the engine, the envelope releases from the sustain
+/// mixer.release(voice);
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum Looping {
+    /// Play the envelope once.
+    #[default]
+    Off,
+    /// Repeat the loop region until the sample is released or stopped, without a loop region the
+    /// sustain is held.
+    Infinite,
+    /// Repeat the loop region an amount of times after it's played the first time, without a loop
+    /// region the envelope is played once.
+    Times(u32),
+}
+
 /// The current state of the ADSR.
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub(crate) enum State {
     Attack,
     Decay(usize),
+    Sustain,
     Release(usize),
     Done,
 }
@@ -18,6 +79,30 @@ pub(crate) struct Envelope {
     sustain_height: f32,
     /// Time it takes to go from the end of the plateau to zero.
     release_slope: f32,
+    /// Offset at which the note is released, until then the envelope holds the sustain height.
+    ///
+    /// When not set the release starts as soon as the sustain height is reached.
+    release_offset: Option<usize>,
+    /// Height from which the release started, lower than the sustain height when released early.
+    release_height: f32,
+    /// The last multiplier applied.
+    level: f32,
+    /// How often the loop region is still repeated.
+    looping: Looping,
+    /// Time at which the loop region starts.
+    loop_start: usize,
+    /// Time at which the loop region ends, when not set there's nothing to repeat.
+    loop_end: Option<usize>,
+    /// The state at the start of the loop region, which is restored at it's end.
+    loop_state: Option<State>,
+    /// Amount of frames the envelope went back in time because of the loops.
+    shift: usize,
+    /// Amount of frames the level glides after going back to the start of the loop region.
+    glide_frames: usize,
+    /// Amount of frames of the glide that are left.
+    glide_left: usize,
+    /// Level at the end of the loop region the glide starts from.
+    glide_height: f32,
 
     /// The current state of the ADSR.
     state: State,
@@ -31,21 +116,72 @@ impl Envelope {
             decay_slope: 1.0 / decay / sustain / sample_rate,
             sustain_height: sustain,
             release_slope: 1.0 / release / sustain / sample_rate,
+            release_offset: None,
+            release_height: sustain,
+            level: 0.0,
+            looping: Looping::Off,
+            loop_start: 0,
+            loop_end: None,
+            loop_state: None,
+            shift: 0,
+            glide_frames: (LOOP_GLIDE_TIME * sample_rate) as usize,
+            glide_left: 0,
+            glide_height: 0.0,
             state: State::Attack,
         }
     }
 
+    /// Hold the sustain height until the offset, after which the release starts.
+    ///
+    /// Can be called while the envelope is running, an offset that already passed releases
+    /// immediately.
+    pub fn release_at(&mut self, offset: usize) {
+        self.release_offset = Some(offset);
+    }
+
+    /// Repeat the part between the start and the end offsets, holding the sustain while there
+    /// are repeats left.
+    ///
+    /// When the end is not after the start there's nothing to repeat.
+    pub fn looping(&mut self, looping: Looping, start: usize, end: usize) {
+        self.looping = looping;
+        self.loop_start = start;
+        self.loop_end = if end > start { Some(end) } else { None };
+    }
+
+    /// The multiplier applied to the last frame.
+    pub fn level(&self) -> f32 {
+        self.level
+    }
+
     /// Apply the envelope on a buffer.
     pub fn apply(&mut self, buffer: &mut [f32], offset: usize) -> State {
         buffer.iter_mut().enumerate().for_each(|(index, tone)| {
             let index_with_offset = index + offset;
-            *tone *= match self.state {
+            let time = index_with_offset - self.shift;
+
+            // Remember where to go back to at the end of the loop region
+            if time == self.loop_start && self.loop_state.is_none() {
+                self.loop_state = Some(self.state);
+            }
+
+            // Release the note when it's let go, from whatever height we are at
+            if let (Some(release_offset), State::Attack | State::Decay(_) | State::Sustain) =
+                (self.release_offset, self.state)
+            {
+                if index_with_offset >= release_offset {
+                    self.state = State::Release(time);
+                    self.release_height = self.level;
+                }
+            }
+
+            let level = match self.state {
                 // Going up
                 State::Attack => {
-                    let multiplier = index_with_offset as f32 * self.attack_slope;
+                    let multiplier = time as f32 * self.attack_slope;
                     if multiplier >= 1.0 {
                         // Move to the new state when we are at the top
-                        self.state = State::Decay(index_with_offset);
+                        self.state = State::Decay(time);
 
                         1.0
                     } else {
@@ -54,22 +190,37 @@ impl Envelope {
                 }
                 // Going down to the middle
                 State::Decay(last_offset) => {
-                    let multiplier =
-                        1.0 - ((index_with_offset - last_offset) as f32 * self.decay_slope);
-                    if multiplier <= self.sustain_height {
-                        // Move to the new state when we are at the sustain height
-                        self.state = State::Release(index_with_offset);
+                    let multiplier = 1.0 - ((time - last_offset) as f32 * self.decay_slope);
+                    // Without a decay time the slope is infinite, go straight to the sustain
+                    if multiplier <= self.sustain_height || !self.decay_slope.is_finite() {
+                        // Move to the new state when we are at the sustain height, hold it when
+                        // the note is released later or when it's still looping
+                        self.state = if self.release_offset.is_some() || self.holds(time) {
+                            State::Sustain
+                        } else {
+                            State::Release(time)
+                        };
 
                         self.sustain_height
                     } else {
                         multiplier
                     }
                 }
+                // Staying in the middle until the note is released or the loop is done
+                State::Sustain => {
+                    if self.release_offset.is_none() && !self.holds(time) {
+                        self.state = State::Release(time);
+                    }
+
+                    self.sustain_height
+                }
                 // Going from the middle to the bottom
                 State::Release(last_offset) => {
-                    let multiplier = self.sustain_height
-                        - ((index_with_offset - last_offset) as f32 * self.release_slope);
-                    if multiplier <= 0.0 {
+                    let multiplier =
+                        self.release_height - ((time - last_offset) as f32 * self.release_slope);
+                    // Without a release time the slope is infinite, on the first frame of the
+                    // release that's multiplied by zero
+                    if multiplier <= 0.0 || !self.release_slope.is_finite() {
                         // We are finished when the multiplier is zero
                         self.state = State::Done;
 
@@ -80,9 +231,147 @@ impl Envelope {
                 }
                 // Nothing left
                 State::Done => 0.0,
+            };
+
+            // Glide from the level at the end of the loop region
+            self.level = if self.glide_left > 0 {
+                let glide = self.glide_left as f32 / self.glide_frames as f32;
+                self.glide_left -= 1;
+
+                level + (self.glide_height - level) * glide
+            } else {
+                level
+            };
+
+            *tone *= self.level;
+
+            // Go back to the start at the end of the loop region, until the note is released
+            if self.loop_end == Some(time + 1)
+                && matches!(self.state, State::Attack | State::Decay(_) | State::Sustain)
+            {
+                self.repeat_loop(time);
             }
         });
 
         self.state
     }
+
+    /// Whether the sustain is held because the loop region is still repeated or played.
+    fn holds(&self, time: usize) -> bool {
+        match (self.looping, self.loop_end) {
+            (Looping::Off, _) => false,
+            (Looping::Infinite, _) => true,
+            (Looping::Times(times), Some(loop_end)) => times > 0 || time + 1 < loop_end,
+            (Looping::Times(_), None) => false,
+        }
+    }
+
+    /// Go back to the state at the start of the loop region when there are repeats left.
+    fn repeat_loop(&mut self, time: usize) {
+        let state = match self.loop_state {
+            Some(state) => state,
+            None => return,
+        };
+        match self.looping {
+            Looping::Off | Looping::Times(0) => return,
+            Looping::Infinite => (),
+            Looping::Times(times) => self.looping = Looping::Times(times - 1),
+        }
+
+        self.state = state;
+        self.shift += time + 1 - self.loop_start;
+        self.glide_height = self.level;
+        self.glide_left = self.glide_frames;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Envelope, Looping, State};
+
+    #[test]
+    fn release_without_release_time() {
+        let mut envelope = Envelope::new(44_100.0, 0.001, 0.001, 0.5, 0.0);
+        envelope.release_at(200);
+
+        let mut buffer = [1.0; 400];
+        assert_eq!(envelope.apply(&mut buffer, 0), State::Done);
+
+        assert!(buffer.iter().all(|tone| tone.is_finite()));
+        assert!(buffer[..200].iter().any(|tone| *tone > 0.0));
+        assert!(buffer[200..].iter().all(|tone| *tone == 0.0));
+    }
+
+    #[test]
+    fn infinite_loop_holds_sustain() {
+        let mut envelope = Envelope::new(44_100.0, 0.2, 0.1, 0.5, 0.3);
+        envelope.looping(Looping::Infinite, 0, 0);
+
+        // Run for 5 seconds
+        let mut buffer = [1.0; 44_100];
+        for second in 0..5 {
+            buffer.iter_mut().for_each(|tone| *tone = 1.0);
+            envelope.apply(&mut buffer, second * 44_100);
+        }
+        assert_eq!(envelope.level(), 0.5);
+
+        // Release from the sustain
+        envelope.release_at(5 * 44_100);
+        assert_eq!(envelope.apply(&mut buffer, 5 * 44_100), State::Done);
+    }
+
+    #[test]
+    fn loop_region_is_repeated() {
+        // Sustain at 0.3 seconds, repeat a region of 0.1 seconds twice so it releases at 0.6
+        let mut envelope = Envelope::new(1_000.0, 0.2, 0.1, 0.5, 0.1);
+        envelope.looping(Looping::Times(2), 300, 400);
+
+        let mut buffer = [1.0; 800];
+        assert_eq!(envelope.apply(&mut buffer, 0), State::Done);
+        assert!(buffer[300..600].iter().all(|tone| *tone == 0.5));
+        assert!(buffer[600] < 0.5);
+        assert!(buffer[700..].iter().all(|tone| *tone == 0.0));
+    }
+
+    #[test]
+    fn loop_region_repeats_the_shape() {
+        // Repeat the attack of 0.1 seconds once, gliding back to it's start
+        let mut envelope = Envelope::new(1_000.0, 0.1, 0.1, 0.5, 0.1);
+        envelope.looping(Looping::Times(1), 0, 100);
+
+        let mut buffer = [1.0; 400];
+        assert_eq!(envelope.apply(&mut buffer, 0), State::Done);
+        assert!((buffer[50] - 0.5).abs() < 0.01);
+        assert!((buffer[130] - 0.3).abs() < 0.01);
+        assert!(buffer[200..230].contains(&1.0));
+        assert!(buffer
+            .windows(2)
+            .all(|tones| (tones[0] - tones[1]).abs() < 0.25));
+    }
+
+    #[test]
+    fn looping_sample_keeps_playing() {
+        let mut engine = crate::Sample::default();
+        engine
+            .env_attack(0.2)
+            .env_release(0.3)
+            .looping(Looping::Infinite);
+
+        let mut mixer = crate::Mixer::default();
+        let voice = mixer.play(engine);
+
+        // Still heard after 3 seconds
+        let mut buffer = [0.0; 4_410];
+        for _ in 0..30 {
+            mixer.generate(&mut buffer);
+        }
+        assert!(buffer.iter().any(|tone| tone.abs() > 0.1));
+
+        // Silent after the release
+        mixer.release(voice);
+        for _ in 0..4 {
+            mixer.generate(&mut buffer);
+        }
+        assert!(buffer.iter().all(|tone| *tone == 0.0));
+    }
 }
@@ -0,0 +1,128 @@
+use crate::{Mixer, Sample, VoiceId};
+use std::time::Duration;
+
+/// A linear ramp of the volume, independent of the envelope.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Fade {
+    /// The current multiplier.
+    level: f32,
+    /// The multiplier the ramp goes to.
+    target: f32,
+    /// How much the multiplier changes every frame.
+    step: f32,
+}
+
+impl Fade {
+    /// A fade at full volume that doesn't change.
+    pub fn new() -> Self {
+        Self {
+            level: 1.0,
+            target: 1.0,
+            step: 0.0,
+        }
+    }
+
+    /// Ramp from the current level to the target in an amount of frames.
+    pub fn to(&mut self, target: f32, frames: usize) {
+        self.target = target;
+        self.step = if frames == 0 {
+            self.level = target;
+
+            0.0
+        } else {
+            (target - self.level).abs() / frames as f32
+        };
+    }
+
+    /// The current multiplier.
+    pub fn level(&self) -> f32 {
+        self.level
+    }
+
+    /// Whether the ramp reached zero and stays there.
+    pub fn is_silent(&self) -> bool {
+        self.level == 0.0 && self.target == 0.0
+    }
+
+    /// Apply the ramp on a buffer.
+    pub fn apply(&mut self, buffer: &mut [f32]) {
+        // Nothing to ramp, only apply the level when it changes anything
+        if self.step == 0.0 {
+            if self.level != 1.0 {
+                buffer.iter_mut().for_each(|tone| *tone *= self.level);
+            }
+
+            return;
+        }
+
+        buffer.iter_mut().for_each(|tone| {
+            *tone *= self.level;
+
+            // Move towards the target and stop at it
+            if self.level < self.target {
+                self.level = (self.level + self.step).min(self.target);
+            } else {
+                self.level = (self.level - self.step).max(self.target);
+            }
+            if self.level == self.target {
+                self.step = 0.0;
+            }
+        });
+    }
+}
+
+impl Mixer {
+    /// Play a sample that ramps up from silence in a duration, independent of it's envelope.
+    ///
+    /// ```rust
+    /// # use std::time::Duration;
+    /// let mut mixer = usfx::Mixer::default();
+    ///
+    /// let mut rain = usfx::Sample::default();
+    /// rain.osc_type(usfx::OscillatorType::Noise).env_sustain(0.3).env_release(60.0);
+    ///
+    /// let ambience = mixer.fade_in(rain, Duration::from_secs(2));
+    /// # let mut buffer = [0.0; 1024];
+    /// # mixer.generate(&mut buffer);
+    ///
+    /// // Stop it smoothly when leaving the area
+    /// mixer.fade_out(ambience, Duration::from_secs(1));
+    /// ```
+    pub fn fade_in(&mut self, sample: Sample, duration: Duration) -> VoiceId {
+        let frames = self.frames(duration);
+
+        let mut generator = self.generator(sample);
+        generator.fade.level = 0.0;
+        generator.fade.to(1.0, frames);
+        let id = generator.id;
+
+        self.add_generator(generator);
+
+        id
+    }
+
+    /// Ramp a playing sample down to silence in a duration after which it's stopped.
+    ///
+    /// Does nothing when the sample already finished.
+    pub fn fade_out(&mut self, voice: VoiceId, duration: Duration) {
+        let frames = self.frames(duration);
+
+        if let Some(generator) = self
+            .generators
+            .iter_mut()
+            .find(|generator| generator.id == voice)
+        {
+            generator.stop(frames);
+        }
+    }
+
+    /// Fade out a playing sample while fading in another one in a duration, such as a new layer
+    /// of the music.
+    ///
+    /// Returns the identifier of the sample that's faded in.
+    pub fn crossfade(&mut self, from: VoiceId, to: Sample, duration: Duration) -> VoiceId {
+        self.fade_out(from, duration);
+
+        self.fade_in(to, duration)
+    }
+}
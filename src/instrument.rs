@@ -0,0 +1,327 @@
+use crate::{BusId, Mixer, Note, Sample};
+
+/// The controller that sets the volume of the instrument.
+const CONTROL_VOLUME: u8 = 7;
+/// The controller that sets the expression, a second volume.
+const CONTROL_EXPRESSION: u8 = 11;
+/// The controller of the sustain pedal, values of 64 and above hold the released notes.
+const CONTROL_SUSTAIN: u8 = 64;
+/// The controller that immediately silences all notes.
+const CONTROL_ALL_SOUND_OFF: u8 = 120;
+/// The controller that releases all notes.
+const CONTROL_ALL_NOTES_OFF: u8 = 123;
+/// The furthest the pitch can be bent in semitones, four octaves up or down.
+const MAX_PITCH_BEND: f32 = 48.0;
+
+/// Identifier of an instrument added to a [`Mixer`].
+///
+/// [`Mixer`]: struct.Mixer.html
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct InstrumentId(usize);
+
+/// An event sent to an instrument, handled the next time the mixer generates sound.
+#[derive(Debug, Copy, Clone)]
+enum Event {
+    NoteOn { note: Note, velocity: f32 },
+    NoteOff { note: Note },
+    Sustain(bool),
+    AllNotesOff,
+    AllSoundOff,
+}
+
+/// A note played by an instrument.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Voice {
+    /// The instrument playing the note.
+    pub(crate) instrument: InstrumentId,
+    /// The MIDI number of the note.
+    pub(crate) key: u8,
+    /// Whether the note is released but held by the sustain pedal.
+    pub(crate) sustained: bool,
+    /// Whether the note is released and playing the release of the envelope.
+    pub(crate) released: bool,
+}
+
+/// A polyphonic synthesizer that plays notes with a sample as it's patch, like a MIDI instrument.
+///
+/// Every note is a voice of the patch at the pitch of the note, it holds the sustain of the
+/// envelope until the note is released.
+/// Add the instrument to a [`Mixer`] to play it, the events are handled the next time the mixer
+/// generates sound.
+///
+/// ```rust
+/// let mut organ = usfx::Sample::default();
+/// organ.osc_type(usfx::OscillatorType::Square).env_sustain(0.8);
+///
+/// let mut mixer = usfx::Mixer::default();
+/// let id = mixer.add_instrument(usfx::Instrument::new(organ));
+///
+/// // Play a C major chord
+/// let instrument = mixer.instrument(id).unwrap();
+/// for note in &["C4", "E4", "G4"] {
+///     instrument.note_on(note.parse().unwrap(), 0.8);
+/// }
+/// # let mut buffer = [0.0; 1024];
+/// mixer.generate(&mut buffer);
+///
+/// // Bend it up a whole tone and let it go
+/// let instrument = mixer.instrument(id).unwrap();
+/// instrument.pitch_bend(2.0);
+/// for note in &["C4", "E4", "G4"] {
+///     instrument.note_off(note.parse().unwrap());
+/// }
+/// mixer.generate(&mut buffer);
+/// ```
+///
+/// [`Mixer`]: struct.Mixer.html
+#[derive(Debug, Clone)]
+pub struct Instrument {
+    /// The sample played for every note.
+    patch: Sample,
+    /// The pitch bend in semitones.
+    bend: f32,
+    /// The values of all controllers.
+    controllers: [u8; 128],
+    /// Events that are not handled yet.
+    events: Vec<Event>,
+    /// Whether the sustain pedal is pressed, updated when the events are handled.
+    sustaining: bool,
+    /// Maximum amount of notes that play at the same time.
+    polyphony: Option<usize>,
+    /// The bus the notes are played on.
+    bus: Option<BusId>,
+}
+
+impl Instrument {
+    /// Create an instrument that plays notes with the sample.
+    pub fn new(patch: Sample) -> Self {
+        let mut controllers = [0; 128];
+        controllers[CONTROL_VOLUME as usize] = 127;
+        controllers[CONTROL_EXPRESSION as usize] = 127;
+
+        Self {
+            patch,
+            bend: 0.0,
+            controllers,
+            events: vec![],
+            sustaining: false,
+            polyphony: None,
+            bus: None,
+        }
+    }
+
+    /// Replace the sample used for new notes, notes already playing keep their sample.
+    pub fn patch(&mut self, patch: Sample) -> &mut Self {
+        self.patch = patch;
+
+        self
+    }
+
+    /// Set the maximum amount of notes that play at the same time, unlimited by default.
+    ///
+    /// Notes that are released still count until their release is finished.
+    /// When a note is played while the maximum is reached a note is stopped with the
+    /// [`StealPolicy`] of the mixer.
+    ///
+    /// [`StealPolicy`]: enum.StealPolicy.html
+    pub fn polyphony(&mut self, notes: usize) -> &mut Self {
+        self.polyphony = Some(notes.max(1));
+
+        self
+    }
+
+    /// Play all notes on a bus of the mixer.
+    pub fn bus(&mut self, bus: BusId) -> &mut Self {
+        self.bus = Some(bus);
+
+        self
+    }
+
+    /// Start playing a note, the velocity is a multiplier of the volume from 0.0-1.0.
+    ///
+    /// When the note is already playing it's released and started again.
+    pub fn note_on(&mut self, note: Note, velocity: f32) -> &mut Self {
+        self.events.push(Event::NoteOn { note, velocity });
+
+        self
+    }
+
+    /// Release a note, the release of the envelope of the patch is played.
+    pub fn note_off(&mut self, note: Note) -> &mut Self {
+        self.events.push(Event::NoteOff { note });
+
+        self
+    }
+
+    /// Bend the pitch of all notes by an amount of semitones, including the notes that are
+    /// playing.
+    ///
+    /// The bend is limited to 48 semitones up or down.
+    pub fn pitch_bend(&mut self, semitones: f32) -> &mut Self {
+        self.bend = if semitones.is_nan() {
+            0.0
+        } else {
+            semitones.clamp(-MAX_PITCH_BEND, MAX_PITCH_BEND)
+        };
+
+        self
+    }
+
+    /// Set the value of a MIDI controller, from 0-127.
+    ///
+    /// The volume (7) and expression (11) controllers set the volume of all notes, the sustain
+    /// pedal (64) holds released notes while it's at 64 or above, all sound off (120) silences
+    /// all notes immediately and all notes off (123) releases them.
+    /// The values of other controllers are stored but don't do anything.
+    pub fn control_change(&mut self, controller: u8, value: u8) -> &mut Self {
+        let controller = controller.min(127);
+        let value = value.min(127);
+
+        match controller {
+            CONTROL_SUSTAIN => self.events.push(Event::Sustain(value >= 64)),
+            CONTROL_ALL_SOUND_OFF => self.events.push(Event::AllSoundOff),
+            CONTROL_ALL_NOTES_OFF => self.events.push(Event::AllNotesOff),
+            _ => (),
+        }
+        self.controllers[controller as usize] = value;
+
+        self
+    }
+
+    /// The value of a MIDI controller.
+    pub fn controller(&self, controller: u8) -> u8 {
+        self.controllers[controller.min(127) as usize]
+    }
+
+    /// Multiplier of the volume of all notes set by the controllers.
+    fn gain(&self) -> f32 {
+        self.controllers[CONTROL_VOLUME as usize] as f32 / 127.0
+            * self.controllers[CONTROL_EXPRESSION as usize] as f32
+            / 127.0
+    }
+}
+
+impl Mixer {
+    /// Add an instrument that can be played with note events.
+    ///
+    /// See [`Instrument`] for an example.
+    ///
+    /// [`Instrument`]: struct.Instrument.html
+    pub fn add_instrument(&mut self, instrument: Instrument) -> InstrumentId {
+        self.instruments.push(instrument);
+
+        InstrumentId(self.instruments.len() - 1)
+    }
+
+    /// Get an instrument to send events to.
+    pub fn instrument(&mut self, id: InstrumentId) -> Option<&mut Instrument> {
+        self.instruments.get_mut(id.0)
+    }
+
+    /// Handle the events of all instruments and update the notes they are playing.
+    pub(crate) fn run_instruments(&mut self) {
+        for index in 0..self.instruments.len() {
+            // Keep the events of paused instruments until they are resumed
+            if !self.scheduler_running(self.instruments[index].bus) {
+                continue;
+            }
+
+            let id = InstrumentId(index);
+            let mut events = std::mem::take(&mut self.instruments[index].events);
+
+            for event in events.drain(..) {
+                match event {
+                    Event::NoteOn { note, velocity } => {
+                        // Play the same note only once
+                        self.release_voices(id, |voice| voice.key == note.midi());
+
+                        let mut sample = self.instruments[index].patch;
+                        sample.note(note);
+                        sample.volume = Some(sample.volume.unwrap_or(1.0) * velocity);
+
+                        let mut generator = self.generator(sample);
+                        generator.bus = self.instruments[index]
+                            .bus
+                            .filter(|bus| self.contains_bus(*bus));
+                        // Hold the note until it's released
+                        generator.envelope.release_at(usize::MAX);
+                        generator.voice = Some(Voice {
+                            instrument: id,
+                            key: note.midi(),
+                            sustained: false,
+                            released: false,
+                        });
+
+                        // Make room for the note, it's not played when there's no room
+                        let room = match self.instruments[index].polyphony {
+                            Some(polyphony) => {
+                                self.steal(polyphony, generator.priority, |generator| {
+                                    generator.voice.is_some_and(|voice| voice.instrument == id)
+                                })
+                            }
+                            None => true,
+                        };
+                        if room {
+                            self.add_generator(generator);
+                        }
+                    }
+                    Event::NoteOff { note } => {
+                        if self.instruments[index].sustaining {
+                            self.voices(id).for_each(|generator| {
+                                if let Some(voice) = &mut generator.voice {
+                                    if !voice.released && voice.key == note.midi() {
+                                        voice.sustained = true;
+                                    }
+                                }
+                            });
+                        } else {
+                            self.release_voices(id, |voice| voice.key == note.midi());
+                        }
+                    }
+                    Event::Sustain(sustaining) => {
+                        self.instruments[index].sustaining = sustaining;
+                        if !sustaining {
+                            self.release_voices(id, |voice| voice.sustained);
+                        }
+                    }
+                    Event::AllNotesOff => self.release_voices(id, |_| true),
+                    Event::AllSoundOff => self
+                        .voices(id)
+                        .for_each(|generator| generator.finished = true),
+                }
+            }
+            self.instruments[index].events = events;
+
+            // Apply the pitch bend and the volume to the notes that are playing
+            let bend = 2.0f32.powf(self.instruments[index].bend / 12.0);
+            let gain = self.instruments[index].gain();
+            self.voices(id).for_each(|generator| {
+                generator.bend = bend;
+                generator.gain = gain;
+            });
+        }
+
+        // Notes that are silenced are removed
+        self.generators.retain(|generator| !generator.finished);
+    }
+
+    /// The generators playing notes of the instrument.
+    fn voices(&mut self, id: InstrumentId) -> impl Iterator<Item = &mut crate::Generator> {
+        self.generators
+            .iter_mut()
+            .filter(move |generator| generator.voice.is_some_and(|voice| voice.instrument == id))
+    }
+
+    /// Release the notes of the instrument that match the filter and are still held.
+    fn release_voices(&mut self, id: InstrumentId, filter: impl Fn(&Voice) -> bool) {
+        self.voices(id).for_each(|generator| {
+            if let Some(voice) = &mut generator.voice {
+                if !voice.released && filter(voice) {
+                    voice.released = true;
+                    voice.sustained = false;
+                    generator.envelope.release_at(generator.offset);
+                }
+            }
+        });
+    }
+}
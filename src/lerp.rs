@@ -0,0 +1,111 @@
+use crate::Sample;
+
+impl Sample {
+    /// Blend between two samples, a `t` of 0.0 results in `a` and 1.0 in `b`.
+    ///
+    /// All continuous parameters such as the frequency, the envelope, the distortion and the
+    /// volume are linearly interpolated.
+    /// Discrete parameters such as the wave type, the duty cycle and the priority are taken from
+    /// `a` when `t` is below 0.5 and from `b` otherwise, the same goes for the repeat and the
+    /// phaser.
+    /// The pitch is blended on an exponential scale: the frequency of `a` is kept and the
+    /// difference is added to the cents, so every blend plays from the same lookup table.
+    /// The value of `t` is clamped to the 0.0-1.0 range.
+    ///
+    /// ```rust
+    /// // An engine sound that goes from idle to full throttle
+    /// let mut idle = usfx::Sample::default();
+    /// idle.osc_frequency(80).osc_type(usfx::OscillatorType::Saw).volume(0.4);
+    ///
+    /// let mut full_throttle = usfx::Sample::default();
+    /// full_throttle.osc_frequency(300).osc_type(usfx::OscillatorType::Saw).volume(0.8);
+    ///
+    /// # let throttle = 0.3;
+    /// let mut mixer = usfx::Mixer::default();
+    /// mixer.play(usfx::Sample::lerp(&idle, &full_throttle, throttle));
+    /// ```
+    pub fn lerp(a: &Sample, b: &Sample, t: f32) -> Sample {
+        let t = t.clamp(0.0, 1.0);
+
+        let lerp = |a: f32, b: f32| a + (b - a) * t;
+        // Pick discrete values by threshold
+        fn pick<T>(t: f32, a: T, b: T) -> T {
+            if t < 0.5 {
+                a
+            } else {
+                b
+            }
+        }
+        // Blend optional values, when only one is set the other is the value that doesn't
+        // change anything
+        let lerp_option = |a: Option<f32>, b: Option<f32>, neutral: f32| match (a, b) {
+            (None, None) => None,
+            (a, b) => Some(lerp(a.unwrap_or(neutral), b.unwrap_or(neutral))),
+        };
+
+        // The distance between the frequencies
+        let octaves = (b.osc_frequency.max(1) as f32 / a.osc_frequency.max(1) as f32).log2();
+
+        Sample {
+            volume: lerp_option(a.volume, b.volume, 1.0),
+            volume_variance: lerp_option(a.volume_variance, b.volume_variance, 0.0),
+            pitch_variance: lerp_option(a.pitch_variance, b.pitch_variance, 0.0),
+            osc_frequency: a.osc_frequency,
+            osc_cents: lerp(a.osc_cents, b.osc_cents) + 1200.0 * octaves * t,
+            osc_type: pick(t, a.osc_type, b.osc_type),
+            osc_duty_cycle: pick(t, a.osc_duty_cycle, b.osc_duty_cycle),
+            mod_mode: pick(t, a.mod_mode, b.mod_mode),
+            mod_type: pick(t, a.mod_type, b.mod_type),
+            mod_ratio: lerp(a.mod_ratio, b.mod_ratio),
+            mod_mix: lerp(a.mod_mix, b.mod_mix),
+            arp_amount: lerp_option(a.arp_amount, b.arp_amount, 1.0),
+            arp_time: lerp(a.arp_time, b.arp_time),
+            repeat_time: match (a.repeat_time, b.repeat_time) {
+                (Some(a), Some(b)) => Some(lerp(a, b)),
+                (a, b) => pick(t, a, b),
+            },
+            env_attack: lerp(a.env_attack, b.env_attack),
+            env_decay: lerp(a.env_decay, b.env_decay),
+            env_release: lerp(a.env_release, b.env_release),
+            env_sustain: lerp(a.env_sustain, b.env_sustain),
+            looping: pick(t, a.looping, b.looping),
+            loop_start: lerp(a.loop_start, b.loop_start),
+            loop_end: lerp(a.loop_end, b.loop_end),
+            pha_offset: match (a.pha_offset, b.pha_offset) {
+                (Some(a), Some(b)) => Some(lerp(a, b)),
+                (a, b) => pick(t, a, b),
+            },
+            pha_sweep: match (a.pha_sweep, b.pha_sweep) {
+                (Some(a), Some(b)) => Some(lerp(a, b)),
+                (a, b) => pick(t, a, b),
+            },
+            dis_crunch: lerp_option(a.dis_crunch, b.dis_crunch, 0.0),
+            dis_drive: lerp_option(a.dis_drive, b.dis_drive, 1.0),
+            priority: pick(t, a.priority, b.priority),
+            category: pick(t, a.category, b.category),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Sample;
+
+    #[test]
+    fn pitch_is_blended_with_cents() {
+        let mut idle = Sample::default();
+        idle.osc_frequency(80).osc_cents(10.0);
+        let mut full_throttle = Sample::default();
+        full_throttle.osc_frequency(320).osc_cents(-10.0);
+
+        for (t, cents) in [(0.0, 10.0), (0.5, 1200.0), (1.0, 2390.0)] {
+            let sample = Sample::lerp(&idle, &full_throttle, t);
+            assert_eq!(sample.osc_frequency, 80);
+            assert!(
+                (sample.osc_cents - cents).abs() < 0.01,
+                "{}",
+                sample.osc_cents
+            );
+        }
+    }
+}
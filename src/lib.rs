@@ -33,17 +33,66 @@
 //! mixer.generate(&mut audio_device_buffer);
 //! ```
 
+mod arpeggio;
+mod bank;
+mod bus;
+mod category;
+mod ducking;
 mod effects;
 mod envelope;
+mod fade;
+mod instrument;
+mod lerp;
+mod midi;
+mod mml;
+mod note;
 mod oscillator;
+mod pause;
+mod polyphony;
+mod preset;
+mod random;
+#[cfg(feature = "serde")]
+mod schema;
+mod sequencer;
+mod sfxr;
+mod song;
+mod validate;
 
-use effects::{distortion::Distortion, Effect};
+use arpeggio::Arpeggio;
+pub use bank::SoundBank;
+use bus::Bus;
+pub use bus::BusId;
+pub use category::Category;
+use ducking::Ducker;
+pub use ducking::Ducking;
+pub use effects::{distortion::Distortion, phaser::Phaser, Effect};
+pub use envelope::Looping;
 use envelope::{Envelope, State};
-use oscillator::Oscillator;
-pub use oscillator::{DutyCycle, OscillatorType};
+use fade::Fade;
+use instrument::Voice;
+pub use instrument::{Instrument, InstrumentId};
+pub use midi::MidiError;
+pub use mml::MmlError;
+pub use note::{Note, NoteError, Tuning};
+pub use oscillator::{DutyCycle, Modulation, OscillatorType};
+use oscillator::{Modulator, Oscillator};
+pub use polyphony::StealPolicy;
+use random::Random;
+#[cfg(feature = "serde")]
+pub use schema::SAMPLE_FORMAT_VERSION;
+use sequencer::Scheduled;
+pub use sequencer::{Pattern, Sequencer};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
-use std::{cell::RefCell, collections::HashMap};
+pub use sfxr::{ImportError, Imported, Unsupported};
+pub use song::Song;
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+pub use validate::SampleError;
 
 /// Audio sample that procedurally generates it's sound.
 ///
@@ -66,20 +115,45 @@ use std::{cell::RefCell, collections::HashMap};
 /// mixer.generate(&mut buffer);
 /// ```
 ///
+/// With the `serde` feature enabled the sample is serialized with a version, see
+/// [`SAMPLE_FORMAT_VERSION`].
+///
 /// [`Generator`]: struct.Generator.html
+/// [`SAMPLE_FORMAT_VERSION`]: constant.SAMPLE_FORMAT_VERSION.html
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(from = "schema::SampleSchema", into = "schema::SampleSchema")
+)]
 #[derive(Debug, Copy, Clone)]
 pub struct Sample {
     volume: Option<f32>,
+    volume_variance: Option<f32>,
+    pitch_variance: Option<f32>,
     osc_frequency: usize,
+    osc_cents: f32,
     osc_type: OscillatorType,
     osc_duty_cycle: DutyCycle,
+    mod_mode: Option<Modulation>,
+    mod_type: OscillatorType,
+    mod_ratio: f32,
+    mod_mix: f32,
+    arp_amount: Option<f32>,
+    arp_time: f32,
+    repeat_time: Option<f32>,
     env_attack: f32,
     env_decay: f32,
     env_release: f32,
     env_sustain: f32,
+    looping: Looping,
+    loop_start: f32,
+    loop_end: f32,
+    pha_offset: Option<f32>,
+    pha_sweep: Option<f32>,
     dis_crunch: Option<f32>,
     dis_drive: Option<f32>,
+    priority: i32,
+    category: Category,
 }
 
 impl Default for Sample {
@@ -87,15 +161,32 @@ impl Default for Sample {
     fn default() -> Self {
         Self {
             volume: None,
+            volume_variance: None,
+            pitch_variance: None,
             osc_frequency: 441,
+            osc_cents: 0.0,
             osc_type: OscillatorType::Sine,
             osc_duty_cycle: DutyCycle::default(),
+            mod_mode: None,
+            mod_type: OscillatorType::Sine,
+            mod_ratio: 2.0,
+            mod_mix: 1.0,
+            arp_amount: None,
+            arp_time: 0.1,
+            repeat_time: None,
             env_attack: 0.01,
             env_decay: 0.1,
             env_sustain: 0.5,
             env_release: 0.5,
+            looping: Looping::Off,
+            loop_start: 0.0,
+            loop_end: 0.0,
+            pha_offset: None,
+            pha_sweep: None,
             dis_crunch: None,
             dis_drive: None,
+            priority: 0,
+            category: Category::default(),
         }
     }
 }
@@ -111,6 +202,48 @@ impl Sample {
         self
     }
 
+    /// Randomly change the volume by up to this amount of decibels every time the sample is
+    /// played.
+    ///
+    /// The change is picked by the random number generator of the [`Mixer`].
+    /// A range from 0.0-6.0 will result in proper behavior, but you can experiment with other
+    /// values.
+    ///
+    /// [`Mixer`]: struct.Mixer.html
+    pub fn volume_variance(&mut self, decibels: f32) -> &mut Self {
+        self.volume_variance = Some(decibels);
+
+        self
+    }
+
+    /// Randomly change the pitch by up to this amount of cents every time the sample is played.
+    ///
+    /// A cent is a hundredth of a semitone.
+    /// The change is picked by the random number generator of the [`Mixer`].
+    /// A range from 0.0-100.0 will result in proper behavior, but you can experiment with other
+    /// values.
+    ///
+    /// ```rust
+    /// // A gunshot that sounds slightly different every time
+    /// let mut gunshot = usfx::Sample::preset_laser(0);
+    /// gunshot.pitch_variance(50.0).volume_variance(2.0);
+    ///
+    /// // Seed the mixer so the variations are the same every run
+    /// let mut mixer = usfx::Mixer::default();
+    /// mixer.seed(42);
+    ///
+    /// for _ in 0..3 {
+    ///     mixer.play(gunshot);
+    /// }
+    /// ```
+    ///
+    /// [`Mixer`]: struct.Mixer.html
+    pub fn pitch_variance(&mut self, cents: f32) -> &mut Self {
+        self.pitch_variance = Some(cents);
+
+        self
+    }
+
     /// Set the frequency of the oscillator in hertz.
     ///
     /// When using the noise oscillator type this will be the seed.
@@ -121,6 +254,43 @@ impl Sample {
         self
     }
 
+    /// Tune the oscillator up or down by an amount of cents, a cent is a hundredth of a semitone.
+    ///
+    /// This allows pitches in between whole hertz, which matters for low notes.
+    pub fn osc_cents(&mut self, cents: f32) -> &mut Self {
+        self.osc_cents = cents;
+
+        self
+    }
+
+    /// Set the frequency of the oscillator to the pitch of a note with the standard tuning where
+    /// A4 is 440 hertz.
+    ///
+    /// The frequency is rounded to whole hertz and the remainder is set with `osc_cents`.
+    ///
+    /// ```rust
+    /// let mut sample = usfx::Sample::default();
+    /// sample.note("C#4".parse().unwrap());
+    /// ```
+    pub fn note(&mut self, note: Note) -> &mut Self {
+        self.note_tuned(note, Tuning::default())
+    }
+
+    /// Set the frequency of the oscillator to the pitch of a note with a custom tuning.
+    ///
+    /// See [`Tuning`] for how to set the reference pitch.
+    ///
+    /// [`Tuning`]: struct.Tuning.html
+    pub fn note_tuned(&mut self, note: Note, tuning: Tuning) -> &mut Self {
+        let frequency = tuning.frequency(note);
+        let rounded = frequency.round().max(1.0);
+
+        self.osc_frequency = rounded as usize;
+        self.osc_cents = 1200.0 * (frequency / rounded).log2();
+
+        self
+    }
+
     /// Set the type of the oscillator.
     ///
     /// See the [`OscillatorType`] enum for supported wave types.
@@ -142,6 +312,76 @@ impl Sample {
         self
     }
 
+    /// Enable the secondary oscillator and set how it modulates the primary oscillator.
+    ///
+    /// See the [`Modulation`] enum for supported modulation modes.
+    ///
+    /// [`Modulation`]: enum.Modulation.html
+    pub fn mod_mode(&mut self, modulation: Modulation) -> &mut Self {
+        self.mod_mode = Some(modulation);
+
+        self
+    }
+
+    /// Set the type of the secondary oscillator.
+    ///
+    /// This only applies when the secondary oscillator is enabled with `mod_mode`.
+    pub fn mod_type(&mut self, oscillator: OscillatorType) -> &mut Self {
+        self.mod_type = oscillator;
+
+        self
+    }
+
+    /// Set the frequency of the secondary oscillator relative to the primary oscillator.
+    ///
+    /// A ratio of 2.0 will let the secondary oscillator run at twice the frequency of the primary
+    /// oscillator.
+    pub fn mod_ratio(&mut self, ratio: f32) -> &mut Self {
+        self.mod_ratio = ratio;
+
+        self
+    }
+
+    /// Set the amount of modulation applied by the secondary oscillator.
+    ///
+    /// A range from 0.0-1.0 will result in proper behavior, but you can experiment with other
+    /// values.
+    pub fn mod_mix(&mut self, mix: f32) -> &mut Self {
+        self.mod_mix = mix;
+
+        self
+    }
+
+    /// Jump the pitch by multiplying the frequency with this amount, like the "change amount" of
+    /// sfxr.
+    ///
+    /// An amount of 2.0 jumps an octave up, 0.5 an octave down.
+    /// The jump happens after the time set with `arp_time`.
+    pub fn arp_amount(&mut self, amount: f32) -> &mut Self {
+        self.arp_amount = Some(amount);
+
+        self
+    }
+
+    /// Set the time in seconds after which the pitch jumps, like the "change speed" of sfxr.
+    ///
+    /// This only applies when the pitch jump is enabled with `arp_amount`.
+    pub fn arp_time(&mut self, time: f32) -> &mut Self {
+        self.arp_time = time;
+
+        self
+    }
+
+    /// Restart the oscillators and the pitch jump every time this amount of seconds passed, like
+    /// the "repeat speed" of sfxr.
+    ///
+    /// The envelope keeps running, this can be used for machine guns, alarms and stutter effects.
+    pub fn repeat_time(&mut self, time: f32) -> &mut Self {
+        self.repeat_time = Some(time);
+
+        self
+    }
+
     /// Set the time until the first envelope slope reaches it's maximum height.
     ///
     /// A range from 0.0-1.0 will result in proper behavior, but you can experiment with other
@@ -182,6 +422,56 @@ impl Sample {
         self
     }
 
+    /// Set how often the loop region of the envelope is repeated, see [`Looping`].
+    ///
+    /// A looping sample holds the sustain of it's envelope while the loop region is repeated,
+    /// with `Looping::Infinite` it keeps playing until it's released with `Mixer::release` or
+    /// faded out.
+    ///
+    /// [`Looping`]: enum.Looping.html
+    pub fn looping(&mut self, looping: Looping) -> &mut Self {
+        self.looping = looping;
+
+        self
+    }
+
+    /// Set the time in seconds from the start of the envelope at which the loop region starts.
+    ///
+    /// The envelope goes back to this point every time the end of the loop region is reached.
+    pub fn loop_start(&mut self, start: f32) -> &mut Self {
+        self.loop_start = start;
+
+        self
+    }
+
+    /// Set the time in seconds from the start of the envelope at which the loop region ends.
+    ///
+    /// When it's not after the loop start there's nothing to repeat.
+    pub fn loop_end(&mut self, end: f32) -> &mut Self {
+        self.loop_end = end;
+
+        self
+    }
+
+    /// Comb filter that adds a delayed copy of the sound, like the "phaser offset" of sfxr.
+    ///
+    /// A range from -1.0-1.0 will result in proper behavior, the sign is only relevant when
+    /// combined with a phaser sweep.
+    pub fn pha_offset(&mut self, offset: f32) -> &mut Self {
+        self.pha_offset = Some(offset);
+
+        self
+    }
+
+    /// Slide the delay of the comb filter over time, like the "phaser sweep" of sfxr.
+    ///
+    /// A range from -1.0-1.0 will result in proper behavior, negative values shorten the delay.
+    pub fn pha_sweep(&mut self, sweep: f32) -> &mut Self {
+        self.pha_sweep = Some(sweep);
+
+        self
+    }
+
     /// Overdrive that adds hard clipping.
     ///
     /// A range from 0.0-1.0 will result in proper behavior, but you can experiment with other
@@ -201,8 +491,39 @@ impl Sample {
 
         self
     }
+
+    /// Set how important the sample is, higher is more important and the default is 0.
+    ///
+    /// When the maximum amount of voices of the [`Mixer`] is reached with the
+    /// `StealPolicy::LowestPriority` policy the voice with the lowest priority is stopped, or the
+    /// sample isn't played at all when all voices are more important.
+    ///
+    /// [`Mixer`]: struct.Mixer.html
+    pub fn priority(&mut self, priority: i32) -> &mut Self {
+        self.priority = priority;
+
+        self
+    }
+
+    /// Set the kind of sound, the [`Mixer`] has a volume and a mute for every category.
+    ///
+    /// See the [`Category`] enum for the categories, the default is `Category::Sfx`.
+    ///
+    /// [`Mixer`]: struct.Mixer.html
+    /// [`Category`]: enum.Category.html
+    pub fn category(&mut self, category: Category) -> &mut Self {
+        self.category = category;
+
+        self
+    }
 }
 
+/// Identifier of a sample played by a [`Mixer`].
+///
+/// [`Mixer`]: struct.Mixer.html
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct VoiceId(usize);
+
 /// Convert samples with PCM.
 ///
 /// This struct is created by [`Sample`].
@@ -212,18 +533,53 @@ impl Sample {
 /// [`Mixer`]: struct.Mixer.html
 #[derive(Debug)]
 struct Generator {
+    /// Identifier returned when it's played.
+    id: VoiceId,
     /// Whether we are finished running the sample.
     pub(crate) finished: bool,
+    /// Amount of frames to wait before starting.
+    delay: usize,
     /// The total offset.
     offset: usize,
+    /// The offset since the oscillators were last restarted.
+    repeat_offset: usize,
+    /// Amount of frames after which the oscillators restart.
+    repeat: Option<usize>,
     /// Multiplier of the result.
     volume: Option<f32>,
+    /// Multiplier of the frequency.
+    rate: f32,
+    /// Multiplier of the frequency set by the instrument playing it.
+    bend: f32,
+    /// Multiplier of the result set by the instrument playing it.
+    gain: f32,
+    /// The note of the instrument it's playing.
+    voice: Option<Voice>,
+    /// Ramp of the volume independent of the envelope.
+    fade: Fade,
+    /// Whether it's fading out, after which it's finished.
+    stopping: bool,
+    /// Whether it's frozen where it is.
+    paused: bool,
+    /// How important it is, the least important is stopped first.
+    priority: i32,
+    /// The kind of sound, used for it's volume.
+    category: Category,
+    /// The bus the result goes into, the output of the mixer when not set.
+    bus: Option<BusId>,
 
     /// The oscillator, because it's a trait it has to be boxed.
     oscillator: Oscillator,
+    /// The secondary oscillator modulating the first.
+    modulator: Option<Modulator>,
+    /// A jump in pitch.
+    arpeggio: Option<Arpeggio>,
     /// The ADSR envelope.
     envelope: Envelope,
 
+    /// Phaser effect.
+    phaser: Option<Phaser>,
+
     /// Distortion effect.
     distortion: Option<Distortion>,
 }
@@ -231,8 +587,50 @@ struct Generator {
 impl Generator {
     /// Generate the sound for the sample.
     fn run(&mut self, output: &mut [f32]) {
-        // Run the oscillator
-        self.oscillator.generate(output, self.offset);
+        // Wait until the generator should start, it can start in the middle of the buffer
+        if self.delay >= output.len() {
+            self.delay -= output.len();
+
+            return;
+        }
+        let output = &mut output[self.delay..];
+        self.delay = 0;
+
+        // Run the oscillators in parts, split where the pitch changes or the oscillators restart
+        let mut start = 0;
+        while start < output.len() {
+            let offset = self.repeat_offset;
+            let remaining = output.len() - start;
+
+            // Run until the next change in pitch, the next restart or until the end of the buffer
+            let length = self
+                .arpeggio
+                .and_then(|arpeggio| arpeggio.frames_until_change(offset))
+                .into_iter()
+                .chain(self.repeat.map(|repeat| repeat - offset))
+                .fold(remaining, usize::min);
+            let rate =
+                self.rate * self.bend * self.arpeggio.map_or(1.0, |arpeggio| arpeggio.rate(offset));
+
+            self.oscillate(&mut output[start..start + length], rate);
+
+            start += length;
+            self.repeat_offset += length;
+
+            // Restart the oscillators and the pitch jump when the repeat time passed
+            if self.repeat == Some(self.repeat_offset) {
+                self.repeat_offset = 0;
+                self.oscillator.reset();
+                if let Some(modulator) = &mut self.modulator {
+                    modulator.reset();
+                }
+            }
+        }
+
+        // Apply the phaser before the envelope like sfxr does
+        if let Some(phaser) = &mut self.phaser {
+            phaser.apply(output, self.offset);
+        }
 
         // Apply the ADSR and set the state if we're finished or not
         if self.envelope.apply(output, self.offset) == State::Done {
@@ -245,12 +643,47 @@ impl Generator {
         }
 
         // Apply the volume
-        if let Some(volume) = self.volume {
+        let volume = self.volume.unwrap_or(1.0) * self.gain;
+        if volume != 1.0 {
             output.iter_mut().for_each(|tone| *tone *= volume);
         }
 
+        // Apply the fade and stop when it's faded out
+        self.fade.apply(output);
+        if self.stopping && self.fade.is_silent() {
+            self.finished = true;
+        }
+
         self.offset += output.len();
     }
+
+    /// Fade out in an amount of frames, after which it's finished.
+    fn stop(&mut self, frames: usize) {
+        self.stopping = true;
+        self.fade.to(0.0, frames);
+
+        // Nothing is heard yet so there's nothing to fade
+        if self.delay > 0 || self.offset == 0 {
+            self.finished = true;
+        }
+    }
+
+    /// Estimate of the current volume, voices that haven't started yet are the loudest.
+    fn loudness(&self) -> f32 {
+        if self.delay > 0 {
+            return f32::INFINITY;
+        }
+
+        self.envelope.level() * self.volume.unwrap_or(1.0) * self.gain * self.fade.level()
+    }
+
+    /// Run the oscillator, optionally modulated by the secondary one.
+    fn oscillate(&mut self, output: &mut [f32], rate: f32) {
+        match &mut self.modulator {
+            Some(modulator) => modulator.generate(&mut self.oscillator, output, rate),
+            None => self.oscillator.generate(output, rate),
+        }
+    }
 }
 
 /// Manage samples and mix the volume output of each.
@@ -282,6 +715,42 @@ pub struct Mixer {
     sample_rate: usize,
     /// A lookup table of oscillator buffers.
     oscillator_lookup: HashMap<(usize, DutyCycle, OscillatorType), RefCell<Vec<f32>>>,
+    /// Buffer the generators write into before they are added to the output.
+    buffer: Vec<f32>,
+    /// Random number generator for the variations of the samples.
+    random: Random,
+    /// Multiplier of the result.
+    volume: f32,
+    /// Named samples that can be played.
+    bank: SoundBank,
+    /// Plays patterns of samples while generating.
+    sequencer: Option<Sequencer>,
+    /// Plays the notes of a song while generating.
+    song: Option<Song>,
+    /// Samples scheduled by the sequencer and the song for the current buffer.
+    scheduled: Vec<Scheduled>,
+    /// Instruments that play notes by events.
+    instruments: Vec<Instrument>,
+    /// Maximum amount of generators that play at the same time.
+    max_voices: Option<usize>,
+    /// Which generator is stopped when there are too many.
+    steal_policy: StealPolicy,
+    /// Multiplier of the result of every category.
+    category_volumes: HashMap<Category, f32>,
+    /// Categories that are silenced.
+    muted_categories: HashSet<Category>,
+    /// Groups of generators with their own volume and effects.
+    buses: Vec<Bus>,
+    /// Buses lowering the volume of other buses.
+    duckers: Vec<Ducker>,
+    /// Identifier of the next generator.
+    next_voice: usize,
+    /// Whether the sequencer, the song and the instruments are paused.
+    paused: bool,
+    /// Unique identifier so buses of other mixers can be recognized.
+    id: usize,
+    /// Which buses are mixed, kept so it's not allocated every time.
+    mixed_buses: Vec<bool>,
 }
 
 impl Mixer {
@@ -293,23 +762,227 @@ impl Mixer {
         }
     }
 
+    /// Seed the random number generator used for the pitch and volume variance of samples.
+    ///
+    /// The same seed with the same calls always results in the same sound.
+    pub fn seed(&mut self, seed: u64) {
+        self.random = Random::new(seed);
+    }
+
+    /// Set the volume which is a multiplier of the combined result of all samples.
+    ///
+    /// A range from 0.0-1.0 will result in proper behavior, but you can experiment with other
+    /// values.
+    pub fn volume(&mut self, volume: f32) {
+        self.volume = volume;
+    }
+
+    /// Use the samples of a sound bank for [`play_named`], replaces the previous bank.
+    ///
+    /// The volume and the seed of the mixer are set when they are defined in the bank.
+    ///
+    /// [`play_named`]: #method.play_named
+    pub fn load_bank(&mut self, bank: SoundBank) {
+        if let Some(volume) = bank.mixer_volume() {
+            self.volume = volume;
+        }
+        if let Some(seed) = bank.mixer_seed() {
+            self.seed(seed);
+        }
+
+        self.bank = bank;
+    }
+
+    /// Play a sample, group or pool by name from the loaded sound bank.
+    ///
+    /// Returns false when the name can't be found in the bank.
+    pub fn play_named(&mut self, name: &str) -> bool {
+        if !self.bank.contains(name) {
+            return false;
+        }
+
+        let mut samples = vec![];
+        self.bank.resolve(name, &mut self.random, &mut samples);
+        for sample in samples {
+            self.play(sample);
+        }
+
+        true
+    }
+
+    /// Play the patterns of a sequencer while generating, replaces the previous sequencer.
+    ///
+    /// See [`Sequencer`] for an example.
+    ///
+    /// [`Sequencer`]: struct.Sequencer.html
+    pub fn load_sequencer(&mut self, sequencer: Sequencer) {
+        self.sequencer = Some(sequencer);
+    }
+
+    /// The sequencer that's playing, can be used to change the tempo while playing.
+    pub fn sequencer(&mut self) -> Option<&mut Sequencer> {
+        self.sequencer.as_mut()
+    }
+
+    /// Stop the sequencer, samples that are already playing keep playing.
+    pub fn stop_sequencer(&mut self) -> Option<Sequencer> {
+        self.sequencer.take()
+    }
+
+    /// Play the notes of a song while generating, replaces the previous song.
+    ///
+    /// See [`Song`] for how the notes are played.
+    ///
+    /// [`Song`]: struct.Song.html
+    pub fn play_song(&mut self, song: Song) {
+        self.song = Some(song);
+    }
+
+    /// The song that's playing.
+    pub fn song(&mut self) -> Option<&mut Song> {
+        self.song.as_mut()
+    }
+
+    /// Stop the song, notes that are already playing keep playing.
+    pub fn stop_song(&mut self) -> Option<Song> {
+        self.song.take()
+    }
+
     /// Play a sample.
-    pub fn play(&mut self, sample: Sample) {
+    ///
+    /// Returns an identifier to control the playing sample, which does nothing when the sample
+    /// isn't played because of the maximum amount of voices.
+    pub fn play(&mut self, sample: Sample) -> VoiceId {
+        let generator = self.generator(sample);
+        let id = generator.id;
+
+        // Use the generator
+        self.add_generator(generator);
+
+        id
+    }
+
+    /// Play a sample after an amount of frames, counted from the start of the next buffer that's
+    /// generated.
+    ///
+    /// The sample starts at the exact frame, even when that's in the middle of a buffer.
+    ///
+    /// ```rust
+    /// let mut mixer = usfx::Mixer::new(44_100);
+    ///
+    /// // Play four blips exactly a tenth of a second apart
+    /// for blip in 0..4 {
+    ///     mixer.play_at(usfx::Sample::preset_blip(0), blip * 4_410);
+    /// }
+    ///
+    /// # let mut buffer = [0.0; 1024];
+    /// mixer.generate(&mut buffer);
+    /// ```
+    pub fn play_at(&mut self, sample: Sample, frame_offset: usize) -> VoiceId {
+        let mut generator = self.generator(sample);
+        generator.delay = frame_offset;
+        let id = generator.id;
+
+        self.add_generator(generator);
+
+        id
+    }
+
+    /// Play a sample after a duration, counted from the start of the next buffer that's generated.
+    ///
+    /// The duration is rounded to the nearest frame, see `play_at`.
+    pub fn play_in(&mut self, sample: Sample, delay: Duration) -> VoiceId {
+        let frame_offset = self.frames(delay);
+
+        self.play_at(sample, frame_offset)
+    }
+
+    /// Play a sample on a bus, the result goes through the volume and effects of the bus.
+    ///
+    /// A bus that's not added to this mixer is ignored, the sample is played on the output then.
+    /// See `add_bus` for an example.
+    pub fn play_on_bus(&mut self, sample: Sample, bus: BusId) -> VoiceId {
+        let mut generator = self.generator(sample);
+        generator.bus = Some(bus).filter(|bus| self.contains_bus(*bus));
+        let id = generator.id;
+
+        self.add_generator(generator);
+
+        id
+    }
+
+    /// Release the envelope of a playing sample, which stops it's loop.
+    ///
+    /// Does nothing when the sample already finished, see [`Looping`] for an example.
+    ///
+    /// [`Looping`]: enum.Looping.html
+    pub fn release(&mut self, voice: VoiceId) {
+        if let Some(generator) = self
+            .generators
+            .iter_mut()
+            .find(|generator| generator.id == voice)
+        {
+            // Nothing is heard yet so there's nothing to release
+            if generator.delay > 0 {
+                generator.finished = true;
+            }
+
+            generator.envelope.release_at(generator.offset);
+        }
+    }
+
+    /// Convert a duration to the nearest amount of frames.
+    fn frames(&self, duration: Duration) -> usize {
+        (duration.as_secs_f64() * self.sample_rate as f64).round() as usize
+    }
+
+    /// Create the generator that plays the sample.
+    fn generator(&mut self, sample: Sample) -> Generator {
+        let id = VoiceId(self.next_voice);
+        self.next_voice += 1;
+
         // Create the ADSR envelope generator
-        let envelope = Envelope::new(
+        let mut envelope = Envelope::new(
             self.sample_rate as f32,
             sample.env_attack,
             sample.env_decay,
             sample.env_sustain,
             sample.env_release,
         );
+        envelope.looping(
+            sample.looping,
+            (sample.loop_start.max(0.0) * self.sample_rate as f32) as usize,
+            (sample.loop_end.max(0.0) * self.sample_rate as f32) as usize,
+        );
 
         // Get the cached buffer (or automatically create a new one)
         let buffer =
             self.oscillator_buffer(sample.osc_frequency, sample.osc_duty_cycle, sample.osc_type);
 
         // Create the oscillator
-        let oscillator = Oscillator::new(buffer, self.sample_rate);
+        let oscillator = Oscillator::new(buffer, self.sample_rate, sample.osc_frequency);
+
+        // Create the secondary oscillator if applicable
+        let modulator = sample.mod_mode.map(|modulation| {
+            let frequency = (sample.osc_frequency as f32 * sample.mod_ratio).round() as usize;
+            let buffer = self.oscillator_buffer(frequency, sample.osc_duty_cycle, sample.mod_type);
+
+            Modulator::new(
+                Oscillator::new(buffer, self.sample_rate, frequency),
+                modulation,
+                sample.mod_mix,
+            )
+        });
+
+        // Create the phaser if applicable
+        let phaser = match (sample.pha_offset, sample.pha_sweep) {
+            (None, None) => None,
+            (offset, sweep) => Some(Phaser::new(
+                offset.unwrap_or(0.0),
+                sweep.unwrap_or(0.0),
+                self.sample_rate,
+            )),
+        };
 
         // Create the distortion if applicable
         let distortion = match (sample.dis_crunch, sample.dis_drive) {
@@ -319,26 +992,62 @@ impl Mixer {
             (None, None) => None,
         };
 
+        // Create the pitch jump if applicable
+        let arpeggio = sample
+            .arp_amount
+            .map(|amount| Arpeggio::new(self.sample_rate as f32, amount, sample.arp_time));
+
+        // Calculate the repeat in frames, it can't be zero otherwise it would never progress
+        let repeat = sample
+            .repeat_time
+            .map(|time| ((time * self.sample_rate as f32) as usize).max(1));
+
+        // Randomly change the pitch and volume if applicable, the pitch on top of the tuning
+        let cents = sample.osc_cents
+            + sample
+                .pitch_variance
+                .map_or(0.0, |cents| self.random.range(-cents, cents));
+        let rate = 2.0f32.powf(cents / 1200.0);
+        let volume = match sample.volume_variance {
+            Some(decibels) => Some(
+                sample.volume.unwrap_or(1.0)
+                    * 10.0f32.powf(self.random.range(-decibels, decibels) / 20.0),
+            ),
+            None => sample.volume,
+        };
+
         // Combine them in a generator
-        let generator = Generator {
+        Generator {
+            id,
             finished: false,
+            delay: 0,
             offset: 0,
-            volume: sample.volume,
+            repeat_offset: 0,
+            repeat,
+            volume,
+            rate,
+            bend: 1.0,
+            gain: 1.0,
+            voice: None,
+            fade: Fade::new(),
+            stopping: false,
+            paused: false,
+            priority: sample.priority,
+            category: sample.category,
+            bus: None,
 
             oscillator,
+            modulator,
+            arpeggio,
             envelope,
 
+            phaser,
             distortion,
-        };
-
-        // Use the generator
-        self.generators.push(generator);
+        }
     }
 
     /// Generate a frame for the sample.
     ///
-    /// The output buffer can be smaller but not bigger than the sample size.
-    ///
     /// ```rust
     /// // Instantiate a new mixer
     /// let mut mixer = usfx::Mixer::default();
@@ -355,25 +1064,108 @@ impl Mixer {
         // Set the buffer to zero
         output.iter_mut().for_each(|tone| *tone = 0.0);
 
-        // If there are no generators just return the empty buffer
+        // Find out which buses are soloed and paused
+        self.prepare_buses(output.len());
+
+        // Play the notes of the instruments
+        self.run_instruments();
+
+        // Start the samples of the sequencer and the song that fall in this buffer, unless they
+        // are paused
+        let sequencer_running = self
+            .sequencer
+            .as_ref()
+            .is_some_and(|sequencer| self.scheduler_running(sequencer.bus));
+        let song_running = self
+            .song
+            .as_ref()
+            .is_some_and(|song| self.scheduler_running(song.bus));
+        if let (Some(sequencer), true) = (&mut self.sequencer, sequencer_running) {
+            sequencer.advance(output.len(), self.sample_rate, &mut self.scheduled);
+        }
+        if let (Some(song), true) = (&mut self.song, song_running) {
+            song.advance(output.len(), self.sample_rate, &mut self.scheduled);
+        }
+        let mut scheduled = std::mem::take(&mut self.scheduled);
+        scheduled.drain(..).for_each(|scheduled| {
+            let mut generator = self.generator(scheduled.sample);
+            generator.delay = scheduled.delay;
+            generator.bus = scheduled.bus.filter(|bus| self.contains_bus(*bus));
+            if let Some(length) = scheduled.length {
+                generator.envelope.release_at(length);
+            }
+
+            self.add_generator(generator);
+        });
+        self.scheduled = scheduled;
+
+        // If there are no generators and no buses just return the empty buffer
         let generators_len = self.generators.len();
-        if generators_len == 0 {
+        if generators_len == 0 && self.buses.is_empty() {
             return;
         }
 
-        // Run the generators
-        self.generators
-            .iter_mut()
-            .for_each(|generator| generator.run(output));
+        let any_solo = self.buses.iter().any(|bus| bus.solo);
+
+        // Run the generators, each in their own buffer so the effects don't leak into the others
+        self.buffer.resize(output.len(), 0.0);
+        let mut rendered = 0;
+        for generator in self.generators.iter_mut() {
+            // Paused generators stay where they are, output nothing and don't count for the volume
+            let running = match generator.bus {
+                Some(bus) => self.buses[bus.index].running,
+                None => true,
+            };
+            if generator.paused || !running {
+                continue;
+            }
+
+            // Generators that start in a later buffer don't count for the volume
+            if generator.delay < self.buffer.len() {
+                rendered += 1;
+            }
+
+            self.buffer.iter_mut().for_each(|tone| *tone = 0.0);
+            generator.run(&mut self.buffer);
+
+            // Muted categories and buses keep running so they continue where they are when
+            // unmuted
+            let audible = match generator.bus {
+                Some(bus) => self.buses[bus.index].audible,
+                None => !any_solo,
+            };
+            let gain = if !audible || self.muted_categories.contains(&generator.category) {
+                0.0
+            } else {
+                self.category_volumes
+                    .get(&generator.category)
+                    .copied()
+                    .unwrap_or(1.0)
+            };
+
+            // Add it to the bus or directly to the output
+            let target = match generator.bus {
+                Some(bus) => &mut self.buses[bus.index].buffer[..],
+                None => &mut output[..],
+            };
+            target
+                .iter_mut()
+                .zip(self.buffer.iter())
+                .for_each(|(tone, generated)| *tone += *generated * gain);
+        }
+
+        // Apply the effects and the volume of the buses
+        self.mix_buses(output);
 
         // Remove the ones that are finished
         self.generators.retain(|generator| !generator.finished);
 
         // Calculate the inverse so we can multiply instead of divide which is more efficient
-        let buffer_len_inv = 1.0 / generators_len as f32;
+        let buffer_len_inv = 1.0 / rendered.max(1) as f32;
 
-        // Divide the generators by the current samples
-        output.iter_mut().for_each(|tone| *tone *= buffer_len_inv);
+        // Divide the generators by the current samples and apply the volume
+        let multiplier = buffer_len_inv * self.volume;
+        output.iter_mut().for_each(|tone| *tone *= multiplier);
     }
 
     /// Retrieve an oscillator buffer or create it when it doesn't exist yet.
@@ -412,6 +1204,9 @@ impl Mixer {
     }
 }
 
+/// Identifier of the next mixer that's created.
+static NEXT_MIXER_ID: AtomicUsize = AtomicUsize::new(0);
+
 impl Default for Mixer {
     /// The default sample rate is 44100.
     fn default() -> Self {
@@ -419,6 +1214,139 @@ impl Default for Mixer {
             sample_rate: 44100,
             generators: vec![],
             oscillator_lookup: HashMap::new(),
+            buffer: vec![],
+            random: Random::new(0),
+            volume: 1.0,
+            bank: SoundBank::default(),
+            sequencer: None,
+            song: None,
+            scheduled: vec![],
+            instruments: vec![],
+            max_voices: None,
+            steal_policy: StealPolicy::default(),
+            category_volumes: HashMap::new(),
+            muted_categories: HashSet::new(),
+            buses: vec![],
+            duckers: vec![],
+            next_voice: 0,
+            paused: false,
+            id: NEXT_MIXER_ID.fetch_add(1, Ordering::Relaxed),
+            mixed_buses: vec![],
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{Mixer, OscillatorType, Pattern, Sample, Sequencer};
+
+    /// A square wave at full volume that's held for a long time.
+    fn square() -> Sample {
+        let mut sample = Sample::default();
+        sample
+            .osc_type(OscillatorType::Square)
+            .env_attack(0.001)
+            .env_decay(0.001)
+            .env_sustain(1.0)
+            .env_release(10.0);
+
+        sample
+    }
+
+    /// The highest value of the next buffer.
+    fn peak(mixer: &mut Mixer) -> f32 {
+        let mut buffer = [0.0; 4_410];
+        mixer.generate(&mut buffer);
+
+        buffer.iter().fold(0.0, |peak, tone| tone.abs().max(peak))
+    }
+
+    #[test]
+    fn pending_voices_dont_lower_the_volume() {
+        let mut mixer = Mixer::default();
+        mixer.play(square());
+        let alone = peak(&mut mixer);
+
+        let mut mixer = Mixer::default();
+        mixer.play(square());
+        mixer.play_at(square(), 44_100 * 5);
+        assert_eq!(peak(&mut mixer), alone);
+    }
+
+    #[test]
+    fn paused_voices_dont_lower_the_volume() {
+        let mut mixer = Mixer::default();
+        mixer.play(square());
+        let alone = peak(&mut mixer);
+
+        let mut mixer = Mixer::default();
+        mixer.play(square());
+        let paused = mixer.play(square());
+        mixer.pause(paused);
+        assert_eq!(peak(&mut mixer), alone);
+
+        let mut mixer = Mixer::default();
+        let bus = mixer.add_bus("paused", None);
+        mixer.play(square());
+        mixer.play_on_bus(square(), bus);
+        mixer.pause_bus(bus, true);
+        assert_eq!(peak(&mut mixer), alone);
+    }
+
+    /// A sequencer playing a short blip on every step.
+    fn sequencer() -> Sequencer {
+        let mut blip = Sample::default();
+        blip.env_release(0.05);
+
+        let mut pattern = Pattern::new(4);
+        (0..4).for_each(|step| {
+            pattern.trigger(step, blip, None, None);
+        });
+        let mut sequencer = Sequencer::new(120.0);
+        sequencer.pattern(pattern);
+
+        sequencer
+    }
+
+    #[test]
+    fn pausing_a_bus_pauses_the_sequencer() {
+        let mut mixer = Mixer::default();
+        let bus = mixer.add_bus("music", None);
+        let mut sequencer = sequencer();
+        sequencer.bus(bus);
+        mixer.load_sequencer(sequencer);
+        peak(&mut mixer);
+        let playing = mixer.generators.len();
+
+        // Pause for 10 seconds
+        mixer.pause_bus(bus, true);
+        for _ in 0..100 {
+            assert_eq!(peak(&mut mixer), 0.0);
+        }
+
+        // Only the voices that were playing continue
+        mixer.pause_bus(bus, false);
+        assert_eq!(mixer.generators.len(), playing);
+        peak(&mut mixer);
+        assert!(mixer.generators.len() <= playing + 1);
+    }
+
+    #[test]
+    fn pausing_everything_pauses_the_sequencer() {
+        let mut mixer = Mixer::default();
+        mixer.load_sequencer(sequencer());
+        peak(&mut mixer);
+        let playing = mixer.generators.len();
+
+        // Pause for 10 seconds
+        mixer.pause_all();
+        for _ in 0..100 {
+            assert_eq!(peak(&mut mixer), 0.0);
+        }
+
+        mixer.resume_all();
+        assert_eq!(mixer.generators.len(), playing);
+        assert!(peak(&mut mixer) > 0.0);
+        assert!(mixer.generators.len() <= playing + 1);
+    }
+}
@@ -0,0 +1,445 @@
+use crate::{
+    song::{Song, SongNote},
+    Note,
+};
+use std::{error::Error, fmt};
+
+/// The tempo when a file doesn't set one, 120 beats per minute.
+const DEFAULT_MICROSECONDS_PER_BEAT: u32 = 500_000;
+
+/// Error when a MIDI file can't be loaded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MidiError {
+    /// The file ended before all data could be read.
+    UnexpectedEnd,
+    /// The file doesn't start with a MIDI header.
+    InvalidHeader,
+    /// The file is not of type 0 or 1, type 2 files with independent sequences are not supported.
+    UnsupportedFormat(u16),
+    /// A data byte was found without a status byte before it.
+    MissingStatus(u8),
+    /// The time division has no ticks per quarter note or per frame, or uses a frame rate other
+    /// than 24, 25, 29.97 or 30 frames per second.
+    InvalidDivision(u16),
+}
+
+impl fmt::Display for MidiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MidiError::UnexpectedEnd => write!(f, "unexpected end of MIDI file"),
+            MidiError::InvalidHeader => write!(f, "invalid MIDI file header"),
+            MidiError::UnsupportedFormat(format) => {
+                write!(f, "unsupported MIDI file format {}", format)
+            }
+            MidiError::MissingStatus(byte) => {
+                write!(f, "MIDI data byte {:#04x} without a status byte", byte)
+            }
+            MidiError::InvalidDivision(division) => {
+                write!(f, "invalid MIDI time division {:#06x}", division)
+            }
+        }
+    }
+}
+
+impl Error for MidiError {}
+
+/// The events that are used from a MIDI file.
+#[derive(Debug, Copy, Clone)]
+enum Event {
+    NoteOn { channel: u8, key: u8, velocity: u8 },
+    NoteOff { channel: u8, key: u8 },
+    ProgramChange { channel: u8, program: u8 },
+    Tempo(u32),
+    EndOfTrack,
+}
+
+impl Song {
+    /// Load a Standard MIDI File of type 0 or 1.
+    ///
+    /// Tempo changes, program changes, the velocity of notes and note offs are used, all other
+    /// events are ignored.
+    /// Assign samples as patches to the channels or programs of the song before playing it.
+    ///
+    /// ```rust
+    /// // A single middle C of a quarter note at 120 beats per minute
+    /// let bytes = [
+    ///     b'M', b'T', b'h', b'd', 0, 0, 0, 6, 0, 0, 0, 1, 0, 96,
+    ///     b'M', b'T', b'r', b'k', 0, 0, 0, 19,
+    ///     0, 0xFF, 0x51, 3, 0x07, 0xA1, 0x20,
+    ///     0, 0x90, 60, 100,
+    ///     96, 0x80, 60, 0,
+    ///     0, 0xFF, 0x2F, 0,
+    /// ];
+    ///
+    /// let mut song = usfx::Song::from_midi(&bytes).unwrap();
+    /// assert_eq!(song.duration(), std::time::Duration::from_millis(500));
+    ///
+    /// // Play everything on the first channel with a square wave
+    /// let mut lead = usfx::Sample::default();
+    /// lead.osc_type(usfx::OscillatorType::Square).env_release(0.1);
+    /// song.channel(0, lead);
+    ///
+    /// let mut mixer = usfx::Mixer::default();
+    /// mixer.play_song(song);
+    /// # let mut buffer = [0.0; 1024];
+    /// mixer.generate(&mut buffer);
+    /// ```
+    pub fn from_midi(bytes: &[u8]) -> Result<Song, MidiError> {
+        let mut reader = Reader { bytes };
+
+        // The header
+        if reader.bytes(4)? != b"MThd" {
+            return Err(MidiError::InvalidHeader);
+        }
+        let length = reader.u32()? as usize;
+        let mut header = Reader {
+            bytes: reader.bytes(length)?,
+        };
+        let format = header.u16()?;
+        if format > 1 {
+            return Err(MidiError::UnsupportedFormat(format));
+        }
+        let tracks = header.u16()?;
+        let division = header.u16()?;
+
+        // Without ticks per quarter note no time can pass
+        if division == 0 {
+            return Err(MidiError::InvalidDivision(division));
+        }
+
+        // SMPTE frames per second and ticks per frame, the frame rate is stored negated and 29
+        // means 29.97 frames per second
+        let smpte = if division & 0x8000 == 0 {
+            None
+        } else {
+            let frames = match -i16::from((division >> 8) as u8 as i8) {
+                29 => 29.97,
+                frames @ (24 | 25 | 30) => frames as f64,
+                _ => return Err(MidiError::InvalidDivision(division)),
+            };
+            let ticks = division & 0xFF;
+            if ticks == 0 {
+                return Err(MidiError::InvalidDivision(division));
+            }
+
+            Some(frames * ticks as f64)
+        };
+
+        // Read the events of all tracks, they are played at the same time
+        let mut events = vec![];
+        let mut read_tracks = 0;
+        while read_tracks < tracks && !reader.bytes.is_empty() {
+            let id = reader.bytes(4)?;
+            let length = reader.u32()? as usize;
+            let chunk = reader.bytes(length)?;
+
+            // Unknown chunks must be skipped
+            if id == b"MTrk" {
+                read_track(chunk, &mut events)?;
+                read_tracks += 1;
+            }
+        }
+        // Keep the order of the events within the same tick
+        events.sort_by_key(|(tick, _)| *tick);
+
+        // Convert the ticks to seconds while following the tempo
+        let seconds_per_tick = |microseconds_per_beat: u32| match smpte {
+            Some(ticks_per_second) => 1.0 / ticks_per_second,
+            // Ticks per beat
+            None => microseconds_per_beat as f64 / 1_000_000.0 / division as f64,
+        };
+
+        let mut notes = vec![];
+        // Notes that didn't receive a note off yet
+        let mut playing: Vec<SongNote> = vec![];
+        let mut programs = [0; 16];
+        let mut tick_duration = seconds_per_tick(DEFAULT_MICROSECONDS_PER_BEAT);
+        let mut last_tick = 0;
+        let mut time = 0.0;
+
+        for (tick, event) in events {
+            time += (tick - last_tick) as f64 * tick_duration;
+            last_tick = tick;
+
+            match event {
+                Event::NoteOn {
+                    channel,
+                    key,
+                    velocity,
+                } => playing.push(SongNote {
+                    start: time,
+                    length: 0.0,
+                    channel,
+                    program: programs[channel as usize],
+                    note: Note::from_midi(key),
+                    velocity: velocity as f32 / 127.0,
+                }),
+                // The earliest note with the same key on the channel is stopped
+                Event::NoteOff { channel, key } => {
+                    if let Some(index) = playing.iter().position(|song_note| {
+                        song_note.channel == channel && song_note.note.midi() == key
+                    }) {
+                        let song_note = playing.remove(index);
+                        notes.push(SongNote {
+                            length: time - song_note.start,
+                            ..song_note
+                        });
+                    }
+                }
+                Event::ProgramChange { channel, program } => programs[channel as usize] = program,
+                Event::Tempo(microseconds_per_beat) => {
+                    tick_duration = seconds_per_tick(microseconds_per_beat)
+                }
+                Event::EndOfTrack => (),
+            }
+        }
+
+        // Stop the notes that are still playing at the end
+        notes.extend(playing.into_iter().map(|song_note| SongNote {
+            length: time - song_note.start,
+            ..song_note
+        }));
+
+        Ok(Song::new(notes, time))
+    }
+}
+
+/// Read the events of a track with their time in ticks.
+fn read_track(bytes: &[u8], events: &mut Vec<(u64, Event)>) -> Result<(), MidiError> {
+    let mut reader = Reader { bytes };
+    let mut tick = 0;
+    // Channel messages can leave out the status byte when it's the same as the previous one
+    let mut running_status = None;
+
+    while !reader.bytes.is_empty() {
+        tick += reader.variable_length()? as u64;
+
+        let byte = reader.u8()?;
+        let (status, first_data) = if byte & 0x80 != 0 {
+            (byte, None)
+        } else {
+            (
+                running_status.ok_or(MidiError::MissingStatus(byte))?,
+                Some(byte),
+            )
+        };
+
+        match status {
+            // Meta events
+            0xFF => {
+                let kind = reader.u8()?;
+                let length = reader.variable_length()? as usize;
+                let data = reader.bytes(length)?;
+
+                match kind {
+                    0x51 if data.len() == 3 => events.push((
+                        tick,
+                        Event::Tempo(u32::from_be_bytes([0, data[0], data[1], data[2]])),
+                    )),
+                    0x2F => {
+                        events.push((tick, Event::EndOfTrack));
+
+                        return Ok(());
+                    }
+                    _ => (),
+                }
+            }
+            // System exclusive messages
+            0xF0 | 0xF7 => {
+                let length = reader.variable_length()? as usize;
+                reader.bytes(length)?;
+            }
+            // Channel messages
+            _ => {
+                running_status = Some(status);
+
+                let channel = status & 0x0F;
+                let first = match first_data {
+                    Some(first) => first,
+                    None => reader.u8()?,
+                };
+
+                match status & 0xF0 {
+                    0x80 => {
+                        reader.u8()?;
+                        events.push((
+                            tick,
+                            Event::NoteOff {
+                                channel,
+                                key: first,
+                            },
+                        ));
+                    }
+                    0x90 => {
+                        let velocity = reader.u8()?;
+                        // A note on without velocity is a note off
+                        events.push((
+                            tick,
+                            if velocity == 0 {
+                                Event::NoteOff {
+                                    channel,
+                                    key: first,
+                                }
+                            } else {
+                                Event::NoteOn {
+                                    channel,
+                                    key: first,
+                                    velocity,
+                                }
+                            },
+                        ));
+                    }
+                    0xC0 => events.push((
+                        tick,
+                        Event::ProgramChange {
+                            channel,
+                            program: first,
+                        },
+                    )),
+                    // Channel pressure only has a single data byte
+                    0xD0 => (),
+                    // Aftertouch, control changes and pitch bends have two
+                    _ => {
+                        reader.u8()?;
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Read big endian values from a byte slice.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    /// Take the next amount of bytes.
+    fn bytes(&mut self, amount: usize) -> Result<&'a [u8], MidiError> {
+        if self.bytes.len() < amount {
+            return Err(MidiError::UnexpectedEnd);
+        }
+
+        let (taken, rest) = self.bytes.split_at(amount);
+        self.bytes = rest;
+
+        Ok(taken)
+    }
+
+    /// Read a byte.
+    fn u8(&mut self) -> Result<u8, MidiError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    /// Read a 16 bits integer.
+    fn u16(&mut self) -> Result<u16, MidiError> {
+        let mut buffer = [0; 2];
+        buffer.copy_from_slice(self.bytes(2)?);
+
+        Ok(u16::from_be_bytes(buffer))
+    }
+
+    /// Read a 32 bits integer.
+    fn u32(&mut self) -> Result<u32, MidiError> {
+        let mut buffer = [0; 4];
+        buffer.copy_from_slice(self.bytes(4)?);
+
+        Ok(u32::from_be_bytes(buffer))
+    }
+
+    /// Read a variable length quantity, 7 bits per byte with the highest bit set when more bytes
+    /// follow.
+    fn variable_length(&mut self) -> Result<u32, MidiError> {
+        let mut value = 0u32;
+        // The value is at most 4 bytes long
+        for _ in 0..4 {
+            let byte = self.u8()?;
+            value = (value << 7) | (byte & 0x7F) as u32;
+            if byte & 0x80 == 0 {
+                break;
+            }
+        }
+
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MidiError;
+    use crate::Song;
+
+    /// A file with a header and a single track with a note.
+    fn file(format: u16, division: u16) -> Vec<u8> {
+        let mut bytes = b"MThd\0\0\0\x06".to_vec();
+        bytes.extend_from_slice(&format.to_be_bytes());
+        bytes.extend_from_slice(&1u16.to_be_bytes());
+        bytes.extend_from_slice(&division.to_be_bytes());
+        bytes.extend_from_slice(b"MTrk\0\0\0\x0C");
+        bytes.extend_from_slice(&[0, 0x90, 60, 100, 0x60, 0x80, 60, 0, 0, 0xFF, 0x2F, 0]);
+
+        bytes
+    }
+
+    #[test]
+    fn valid() {
+        let song = Song::from_midi(&file(0, 96)).unwrap();
+        assert_eq!(song.duration().as_millis(), 500);
+
+        // 25 frames per second with 40 ticks per frame is a millisecond per tick
+        let song = Song::from_midi(&file(1, 0xE728)).unwrap();
+        assert_eq!(song.duration().as_millis(), 96);
+    }
+
+    #[test]
+    fn invalid_header() {
+        assert_eq!(
+            Song::from_midi(b"RIFF\0\0\0\x06\0\0\0\x01\0\x60").err(),
+            Some(MidiError::InvalidHeader)
+        );
+        assert_eq!(
+            Song::from_midi(b"MThd").err(),
+            Some(MidiError::UnexpectedEnd)
+        );
+        assert_eq!(
+            Song::from_midi(b"MThd\0\0\0\x06\0\0").err(),
+            Some(MidiError::UnexpectedEnd)
+        );
+        assert_eq!(
+            Song::from_midi(&file(2, 96)).err(),
+            Some(MidiError::UnsupportedFormat(2))
+        );
+    }
+
+    #[test]
+    fn invalid_division() {
+        for division in [0, 0x8004, 0x8000 | (-23i8 as u8 as u16) << 8 | 4, 0xE700] {
+            assert_eq!(
+                Song::from_midi(&file(0, division)).err(),
+                Some(MidiError::InvalidDivision(division))
+            );
+        }
+    }
+
+    #[test]
+    fn truncated_track() {
+        let bytes = file(0, 96);
+        assert_eq!(
+            Song::from_midi(&bytes[..bytes.len() - 2]).err(),
+            Some(MidiError::UnexpectedEnd)
+        );
+    }
+
+    #[test]
+    fn missing_status() {
+        let mut bytes = file(0, 96);
+        // Replace the first status byte by a data byte
+        bytes[23] = 0x40;
+        assert_eq!(
+            Song::from_midi(&bytes).err(),
+            Some(MidiError::MissingStatus(0x40))
+        );
+    }
+}
@@ -0,0 +1,395 @@
+use crate::{
+    song::{Song, SongNote},
+    Note,
+};
+use std::{error::Error, fmt};
+
+/// The tempo when a song doesn't set one, in quarter notes per minute.
+const DEFAULT_TEMPO: f64 = 120.0;
+/// The octave when a channel doesn't set one.
+const DEFAULT_OCTAVE: i32 = 4;
+/// The highest volume of the `v` command.
+const MAX_VOLUME: u32 = 15;
+/// The highest gate time of the `q` command, where the note is held for it's whole length.
+const MAX_QUANTIZE: u32 = 8;
+/// The amount of times a loop is played when no count is given.
+const DEFAULT_LOOP_COUNT: u32 = 2;
+/// The most commands a whole song plays with the loops repeated, so a huge loop count can't hang.
+const MAX_COMMANDS: usize = 1_000_000;
+
+/// Error when a Music Macro Language string can't be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MmlError {
+    /// A character that's not a command.
+    UnexpectedCharacter {
+        /// The character.
+        character: char,
+        /// The byte position in the string.
+        position: usize,
+    },
+    /// A command that needs a number doesn't have one, or it's zero when that's not allowed.
+    InvalidNumber {
+        /// The command.
+        command: char,
+        /// The byte position in the string.
+        position: usize,
+    },
+    /// A note is outside of the MIDI range from C-1 to G9.
+    NoteOutOfRange(usize),
+    /// A loop is not closed or closed without being opened, contains the byte position.
+    UnmatchedLoop(usize),
+    /// The loops repeat more than a million commands, contains the byte position of the command
+    /// that went over.
+    TooLong(usize),
+}
+
+impl fmt::Display for MmlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MmlError::UnexpectedCharacter {
+                character,
+                position,
+            } => write!(
+                f,
+                "unexpected character '{}' at position {}",
+                character, position
+            ),
+            MmlError::InvalidNumber { command, position } => write!(
+                f,
+                "invalid number for command '{}' at position {}",
+                command, position
+            ),
+            MmlError::NoteOutOfRange(position) => {
+                write!(f, "note at position {} is out of range", position)
+            }
+            MmlError::UnmatchedLoop(position) => {
+                write!(f, "unmatched loop at position {}", position)
+            }
+            MmlError::TooLong(position) => {
+                write!(f, "song is too long at position {}", position)
+            }
+        }
+    }
+}
+
+impl Error for MmlError {}
+
+impl Song {
+    /// Parse a song written in Music Macro Language.
+    ///
+    /// Channels are separated by `;`, the first channel is 0.
+    /// Assign samples as patches to the channels before playing it.
+    /// All commands are case insensitive and whitespace is ignored:
+    ///
+    /// | Command | Meaning |
+    /// |---|---|
+    /// | `c` `d` `e` `f` `g` `a` `b` | Play a note, followed by `+` or `#` for sharp, `-` for flat, an optional length and dots |
+    /// | `r` | Rest, followed by an optional length and dots |
+    /// | `o4` | Set the octave, `c` in octave 4 is middle C |
+    /// | `>` `<` | Go an octave up or down |
+    /// | `l8` | Set the length of notes without a length, 4 is a quarter note, dots are allowed |
+    /// | `t120` | Set the tempo in quarter notes per minute for all channels |
+    /// | `v15` | Set the volume from 0-15 |
+    /// | `q8` | Set the part of the length the note is held from 1-8, the rest is the release |
+    /// | `[` `]2` | Repeat the part between the brackets an amount of times, 2 by default |
+    ///
+    /// A song can play at most a million commands with all loops repeated.
+    ///
+    /// ```rust
+    /// let mut song = usfx::Song::from_mml("t120 o4 l8 cdefgab>c; o2 l2 c g").unwrap();
+    ///
+    /// let mut lead = usfx::Sample::default();
+    /// lead.osc_type(usfx::OscillatorType::Square).env_release(0.1);
+    /// let mut bass = usfx::Sample::default();
+    /// bass.osc_type(usfx::OscillatorType::Triangle);
+    /// song.channel(0, lead).channel(1, bass);
+    ///
+    /// assert_eq!(song.duration(), std::time::Duration::from_secs(2));
+    ///
+    /// let mut mixer = usfx::Mixer::default();
+    /// mixer.play_song(song);
+    /// # let mut buffer = [0.0; 1024];
+    /// mixer.generate(&mut buffer);
+    /// ```
+    pub fn from_mml(mml: &str) -> Result<Song, MmlError> {
+        let mut notes = vec![];
+        let mut tempos = vec![];
+        let mut end: f64 = 0.0;
+
+        let mut offset = 0;
+        let mut commands = MAX_COMMANDS;
+        for (channel, part) in mml.split(';').enumerate() {
+            let mut parser = Parser::new(part, offset, channel.min(u8::MAX as usize) as u8);
+            parser.parse(&mut notes, &mut tempos, &mut commands)?;
+            end = end.max(parser.beat);
+
+            // Skip the separator
+            offset += part.len() + 1;
+        }
+
+        // Convert the beats to seconds following the tempo changes of all channels
+        tempos.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        let seconds = |beat: f64| {
+            let mut seconds = 0.0;
+            let mut last_beat = 0.0;
+            let mut tempo = DEFAULT_TEMPO;
+            for &(change, new_tempo) in tempos.iter().take_while(|(change, _)| *change < beat) {
+                seconds += (change - last_beat) * 60.0 / tempo;
+                last_beat = change;
+                tempo = new_tempo;
+            }
+
+            seconds + (beat - last_beat) * 60.0 / tempo
+        };
+
+        let notes = notes
+            .into_iter()
+            .map(|song_note: SongNote| {
+                // The start and the length are in beats until now
+                let start = seconds(song_note.start);
+                SongNote {
+                    start,
+                    length: seconds(song_note.start + song_note.length) - start,
+                    ..song_note
+                }
+            })
+            .collect();
+
+        Ok(Song::new(notes, seconds(end)))
+    }
+}
+
+/// Parse a single channel.
+struct Parser {
+    /// The characters with their byte position in the whole string.
+    characters: Vec<(usize, char)>,
+    /// Index of the next character.
+    index: usize,
+    /// The channel the notes are played on.
+    channel: u8,
+
+    /// The current time in quarter note beats.
+    beat: f64,
+    /// The current octave.
+    octave: i32,
+    /// The length of notes without a length in beats.
+    length: f64,
+    /// Volume from 0.0-1.0.
+    volume: f32,
+    /// Part of the length the note is held.
+    quantize: f64,
+    /// The index after the opening bracket of the loops we are in with the amount of times they
+    /// still have to be repeated, which is set at the first closing bracket.
+    loops: Vec<(usize, Option<u32>)>,
+}
+
+impl Parser {
+    /// Create a parser for a channel that starts at the byte offset in the whole string.
+    fn new(mml: &str, offset: usize, channel: u8) -> Self {
+        Self {
+            characters: mml
+                .char_indices()
+                .filter(|(_, character)| !character.is_whitespace())
+                .map(|(position, character)| (position + offset, character.to_ascii_lowercase()))
+                .collect(),
+            index: 0,
+            channel,
+            beat: 0.0,
+            octave: DEFAULT_OCTAVE,
+            length: 1.0,
+            volume: 1.0,
+            quantize: 1.0,
+            loops: vec![],
+        }
+    }
+
+    /// Parse all commands until the amount of commands left runs out, the notes are timed in
+    /// beats.
+    fn parse(
+        &mut self,
+        notes: &mut Vec<SongNote>,
+        tempos: &mut Vec<(f64, f64)>,
+        commands: &mut usize,
+    ) -> Result<(), MmlError> {
+        while let Some((position, command)) = self.next() {
+            *commands = commands.checked_sub(1).ok_or(MmlError::TooLong(position))?;
+
+            match command {
+                'c' | 'd' | 'e' | 'f' | 'g' | 'a' | 'b' => {
+                    let mut semitone = match command {
+                        'c' => 0,
+                        'd' => 2,
+                        'e' => 4,
+                        'f' => 5,
+                        'g' => 7,
+                        'a' => 9,
+                        _ => 11,
+                    };
+                    while let Some(accidental) = self.accidental() {
+                        semitone += accidental;
+                    }
+                    let length = self.length(command, position)?;
+
+                    let midi = (self.octave + 1) * 12 + semitone;
+                    if !(0..=127).contains(&midi) {
+                        return Err(MmlError::NoteOutOfRange(position));
+                    }
+
+                    notes.push(SongNote {
+                        start: self.beat,
+                        length: length * self.quantize,
+                        channel: self.channel,
+                        program: 0,
+                        note: Note::from_midi(midi as u8),
+                        velocity: self.volume,
+                    });
+                    self.beat += length;
+                }
+                'r' | 'p' => self.beat += self.length(command, position)?,
+                'o' => self.octave = self.number(command, position)? as i32,
+                '>' => self.octave += 1,
+                '<' => self.octave -= 1,
+                'l' => {
+                    let length = self.number(command, position)?;
+                    self.length = self.dotted(note_length(length, command, position)?);
+                }
+                't' => match self.number(command, position)? {
+                    0 => return Err(MmlError::InvalidNumber { command, position }),
+                    tempo => tempos.push((self.beat, tempo as f64)),
+                },
+                'v' => {
+                    self.volume =
+                        self.number(command, position)?.min(MAX_VOLUME) as f32 / MAX_VOLUME as f32
+                }
+                'q' => match self.number(command, position)? {
+                    0 => return Err(MmlError::InvalidNumber { command, position }),
+                    quantize => {
+                        self.quantize = quantize.min(MAX_QUANTIZE) as f64 / MAX_QUANTIZE as f64
+                    }
+                },
+                '[' => self.loops.push((self.index, None)),
+                ']' => {
+                    let count = self.optional_number().unwrap_or(DEFAULT_LOOP_COUNT);
+                    let (start, remaining) =
+                        self.loops.pop().ok_or(MmlError::UnmatchedLoop(position))?;
+
+                    // The first time we get here the loop already played once
+                    let remaining = remaining.unwrap_or(count.saturating_sub(1));
+                    if remaining > 0 {
+                        self.loops.push((start, Some(remaining - 1)));
+                        self.index = start;
+                    }
+                }
+                character => {
+                    return Err(MmlError::UnexpectedCharacter {
+                        character,
+                        position,
+                    })
+                }
+            }
+        }
+
+        match self.loops.first() {
+            // Report the position of the opening bracket
+            Some((start, _)) => Err(MmlError::UnmatchedLoop(self.characters[start - 1].0)),
+            None => Ok(()),
+        }
+    }
+
+    /// Take the next character.
+    fn next(&mut self) -> Option<(usize, char)> {
+        let next = self.characters.get(self.index).copied();
+        self.index += 1;
+
+        next
+    }
+
+    /// Look at the next character.
+    fn peek(&self) -> Option<char> {
+        self.characters
+            .get(self.index)
+            .map(|(_, character)| *character)
+    }
+
+    /// Take a sharp or a flat.
+    fn accidental(&mut self) -> Option<i32> {
+        let accidental = match self.peek()? {
+            '+' | '#' => 1,
+            '-' => -1,
+            _ => return None,
+        };
+        self.index += 1;
+
+        Some(accidental)
+    }
+
+    /// Take a number when there is one.
+    fn optional_number(&mut self) -> Option<u32> {
+        let mut number: Option<u32> = None;
+        while let Some(digit) = self.peek().and_then(|character| character.to_digit(10)) {
+            number = Some(number.unwrap_or(0).saturating_mul(10).saturating_add(digit));
+            self.index += 1;
+        }
+
+        number
+    }
+
+    /// Take a number that's required by a command.
+    fn number(&mut self, command: char, position: usize) -> Result<u32, MmlError> {
+        self.optional_number()
+            .ok_or(MmlError::InvalidNumber { command, position })
+    }
+
+    /// Take the optional length with dots of a note or a rest in beats.
+    fn length(&mut self, command: char, position: usize) -> Result<f64, MmlError> {
+        match self.optional_number() {
+            Some(length) => Ok(self.dotted(note_length(length, command, position)?)),
+            // Dots can also follow a note without a length
+            None => Ok(self.dotted(self.length)),
+        }
+    }
+
+    /// Take the dots after a length, every dot adds half of the previous addition.
+    fn dotted(&mut self, length: f64) -> f64 {
+        let mut addition = length / 2.0;
+        let mut length = length;
+        while self.peek() == Some('.') {
+            length += addition;
+            addition /= 2.0;
+            self.index += 1;
+        }
+
+        length
+    }
+}
+
+/// Convert a length like 4 for a quarter note to beats.
+fn note_length(length: u32, command: char, position: usize) -> Result<f64, MmlError> {
+    match length {
+        0 => Err(MmlError::InvalidNumber { command, position }),
+        length => Ok(4.0 / length as f64),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MmlError;
+    use crate::Song;
+    use std::time::Duration;
+
+    #[test]
+    fn loops_are_repeated() {
+        let song = Song::from_mml("l4 [c [d]3 ]2 e").unwrap();
+        assert_eq!(song.duration(), Duration::from_millis(4500));
+    }
+
+    #[test]
+    fn huge_loops_are_too_long() {
+        for mml in ["[c]4294967295", "[]4294967295", "[[[r]9999]9999]9999"] {
+            assert!(matches!(
+                Song::from_mml(mml).err(),
+                Some(MmlError::TooLong(_))
+            ));
+        }
+    }
+}
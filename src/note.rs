@@ -0,0 +1,188 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::{error::Error, fmt, str::FromStr};
+
+/// The names of the notes in an octave, starting at C.
+const NAMES: [&str; 12] = [
+    "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+];
+/// The MIDI number of A4, the note the tuning is based on.
+const REFERENCE_MIDI: u8 = 69;
+/// The highest MIDI number.
+const MAX_MIDI: u8 = 127;
+
+/// Error when a note name can't be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NoteError {
+    /// The name doesn't follow the format of a letter, optional accidentals and an octave such as
+    /// `"C#4"`.
+    InvalidName(String),
+    /// The note is outside of the MIDI range from C-1 to G9.
+    OutOfRange(String),
+}
+
+impl fmt::Display for NoteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NoteError::InvalidName(name) => write!(f, "invalid note name \"{}\"", name),
+            NoteError::OutOfRange(name) => {
+                write!(f, "note \"{}\" is outside of the range C-1 to G9", name)
+            }
+        }
+    }
+}
+
+impl Error for NoteError {}
+
+/// A musical note, a MIDI note number with an optional offset in cents.
+///
+/// Notes can be created from MIDI numbers or parsed from names such as `"C#4"` or `"Db4"`, where
+/// A4 is MIDI number 69 and C-1 is 0.
+///
+/// ```rust
+/// let note: usfx::Note = "A4".parse().unwrap();
+/// assert_eq!(note, usfx::Note::from_midi(69));
+/// assert_eq!(note.frequency(), 440.0);
+///
+/// // Play a slightly detuned C5
+/// let mut sample = usfx::Sample::default();
+/// sample.note(usfx::Note::from_midi(72).detune(-10.0));
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Note {
+    midi: u8,
+    cents: f32,
+}
+
+impl Note {
+    /// Create a note from a MIDI note number, numbers above 127 are clamped.
+    pub fn from_midi(midi: u8) -> Self {
+        Self {
+            midi: midi.min(MAX_MIDI),
+            cents: 0.0,
+        }
+    }
+
+    /// Offset the pitch by an amount of cents, a cent is a hundredth of a semitone.
+    pub fn detune(self, cents: f32) -> Self {
+        Self { cents, ..self }
+    }
+
+    /// Move the note up or down by an amount of semitones, clamped to the MIDI range.
+    pub fn transpose(self, semitones: i32) -> Self {
+        Self {
+            midi: (self.midi as i32 + semitones).clamp(0, MAX_MIDI as i32) as u8,
+            ..self
+        }
+    }
+
+    /// The MIDI note number.
+    pub fn midi(&self) -> u8 {
+        self.midi
+    }
+
+    /// The offset in cents.
+    pub fn cents(&self) -> f32 {
+        self.cents
+    }
+
+    /// The frequency in hertz with the standard tuning where A4 is 440 hertz.
+    pub fn frequency(&self) -> f32 {
+        Tuning::default().frequency(*self)
+    }
+}
+
+impl FromStr for Note {
+    type Err = NoteError;
+
+    /// Parse a note name such as `"C4"`, `"F#3"` or `"Bb-1"`.
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        let invalid = || NoteError::InvalidName(name.to_string());
+
+        let mut chars = name.chars();
+        let letter = chars.next().ok_or_else(invalid)?;
+        let mut semitone: i32 = match letter.to_ascii_uppercase() {
+            'C' => 0,
+            'D' => 2,
+            'E' => 4,
+            'F' => 5,
+            'G' => 7,
+            'A' => 9,
+            'B' => 11,
+            _ => return Err(invalid()),
+        };
+
+        // Any amount of sharps and flats can follow the letter
+        let rest = chars.as_str();
+        let octave = rest.trim_start_matches(['#', 'b']);
+        rest[..rest.len() - octave.len()]
+            .chars()
+            .for_each(|accidental| semitone += if accidental == '#' { 1 } else { -1 });
+
+        let octave: i32 = octave.parse().map_err(|_| invalid())?;
+
+        // C-1 is the first MIDI note
+        let midi = (octave + 1) * 12 + semitone;
+        if (0..=MAX_MIDI as i32).contains(&midi) {
+            Ok(Self::from_midi(midi as u8))
+        } else {
+            Err(NoteError::OutOfRange(name.to_string()))
+        }
+    }
+}
+
+impl fmt::Display for Note {
+    /// Show the name with sharps, such as `"C#4"`, followed by the cents if there are any.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{}",
+            NAMES[self.midi as usize % 12],
+            self.midi as i32 / 12 - 1
+        )?;
+
+        if self.cents != 0.0 {
+            write!(f, "{:+}c", self.cents)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The reference pitch used to convert notes to frequencies.
+///
+/// ```rust
+/// // Baroque tuning
+/// let tuning = usfx::Tuning::new(415.0);
+/// let note: usfx::Note = "A4".parse().unwrap();
+///
+/// let mut sample = usfx::Sample::default();
+/// sample.note_tuned(note, tuning);
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Tuning {
+    reference: f32,
+}
+
+impl Tuning {
+    /// Create an equal temperament tuning where A4 has this frequency in hertz.
+    pub fn new(reference: f32) -> Self {
+        Self { reference }
+    }
+
+    /// The frequency of a note in hertz.
+    pub fn frequency(&self, note: Note) -> f32 {
+        let semitones = note.midi as f32 - REFERENCE_MIDI as f32 + note.cents / 100.0;
+
+        self.reference * 2.0f32.powf(semitones / 12.0)
+    }
+}
+
+impl Default for Tuning {
+    /// The default tuning is A4 at 440 hertz.
+    fn default() -> Self {
+        Self { reference: 440.0 }
+    }
+}
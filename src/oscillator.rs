@@ -118,6 +118,24 @@ impl OscillatorType {
     }
 }
 
+/// How the secondary oscillator changes the primary oscillator.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub enum Modulation {
+    /// Restart the primary oscillator every time the secondary oscillator completes a cycle.
+    ///
+    /// The mix determines how far the phase is reset, a mix of 1.0 is a classic hard sync.
+    HardSync,
+    /// Multiply the primary oscillator with the secondary oscillator.
+    ///
+    /// Creates metallic, bell-like sounds.
+    Ring,
+    /// Multiply the primary oscillator with the secondary oscillator moved to the 0.0-1.0 range.
+    ///
+    /// Creates a tremolo at low ratios.
+    Amplitude,
+}
+
 /// The oscillator just loops through the already populated lookup table.
 #[derive(Debug)]
 pub(crate) struct Oscillator {
@@ -125,23 +143,173 @@ pub(crate) struct Oscillator {
     lut: RefCell<Vec<f32>>,
     /// The sample rate, also half the size of the lookup table.
     sample_rate: usize,
+    /// The frequency the lookup table is built with, used to detect when a cycle is completed.
+    frequency: usize,
+    /// The current position in the lookup table.
+    position: f32,
 }
 
 impl Oscillator {
     /// Instantiate a new oscillator that uses the passed lookup table.
-    pub(crate) fn new(lut: RefCell<Vec<f32>>, sample_rate: usize) -> Self {
-        Self { lut, sample_rate }
+    pub(crate) fn new(lut: RefCell<Vec<f32>>, sample_rate: usize, frequency: usize) -> Self {
+        Self {
+            lut,
+            sample_rate,
+            frequency,
+            position: 0.0,
+        }
     }
 
     /// Fill the output buffer with generated sound.
-    pub(crate) fn generate(&mut self, output: &mut [f32], offset: usize) {
-        let rotating_index = offset % self.sample_rate;
+    ///
+    /// The rate is a multiplier of the frequency.
+    pub(crate) fn generate(&mut self, output: &mut [f32], rate: f32) {
+        if rate != 1.0 || self.position.fract() != 0.0 {
+            output.iter_mut().for_each(|tone| *tone += self.next(rate));
+
+            return;
+        }
 
         // Combine the size of the output buffer with the size of the cached frequencies buffer and
-        // add the frequencies to the output
-        output
-            .iter_mut()
-            .zip(self.lut.borrow()[rotating_index..].iter())
-            .for_each(|(old, new)| *old += *new);
+        // add the frequencies to the output, the lookup table is only twice the size of the sample
+        // rate so bigger buffers are split
+        output.chunks_mut(self.sample_rate).for_each(|chunk| {
+            let rotating_index = self.position as usize;
+
+            chunk
+                .iter_mut()
+                .zip(self.lut.borrow()[rotating_index..].iter())
+                .for_each(|(old, new)| *old += *new);
+
+            self.position = ((rotating_index + chunk.len()) % self.sample_rate) as f32;
+        });
+    }
+
+    /// Get the next value and move the position forward.
+    pub(crate) fn next(&mut self, rate: f32) -> f32 {
+        let tone = self.lut.borrow()[self.position as usize];
+
+        self.advance(rate);
+
+        tone
+    }
+
+    /// Move the position forward, returns whether a cycle was completed.
+    pub(crate) fn advance(&mut self, rate: f32) -> bool {
+        let cycle = self.cycle();
+
+        // Any rate wraps around, also higher than the table or negative ones
+        self.position = (self.position + rate).rem_euclid(self.sample_rate as f32);
+        // A tiny negative position is rounded up to the end of the table, an infinite one is NaN
+        if self.position >= self.sample_rate as f32 || self.position.is_nan() {
+            self.position = 0.0;
+        }
+
+        cycle != self.cycle()
+    }
+
+    /// Move the phase within the current cycle back towards the start of the cycle.
+    ///
+    /// An amount of 1.0 restarts the cycle completely.
+    pub(crate) fn sync(&mut self, amount: f32) {
+        // The lookup table contains a whole amount of cycles
+        let period = self.sample_rate as f32 / self.frequency.max(1) as f32;
+        let phase = self.position % period;
+
+        self.position -= phase * amount.clamp(0.0, 1.0);
+    }
+
+    /// Restart the oscillator from the beginning.
+    pub(crate) fn reset(&mut self) {
+        self.position = 0.0;
+    }
+
+    /// The index of the cycle the position is in.
+    fn cycle(&self) -> usize {
+        (self.position * self.frequency as f32 / self.sample_rate as f32) as usize
+    }
+}
+
+/// A secondary oscillator that modulates the primary oscillator.
+#[derive(Debug)]
+pub(crate) struct Modulator {
+    /// The secondary oscillator.
+    oscillator: Oscillator,
+    /// How the primary oscillator is changed.
+    modulation: Modulation,
+    /// The amount of modulation.
+    mix: f32,
+}
+
+impl Modulator {
+    /// Instantiate a new modulator with an oscillator for the secondary wave.
+    pub(crate) fn new(oscillator: Oscillator, modulation: Modulation, mix: f32) -> Self {
+        Self {
+            oscillator,
+            modulation,
+            mix,
+        }
+    }
+
+    /// Fill the output buffer with the primary oscillator modulated by the secondary one.
+    pub(crate) fn generate(&mut self, primary: &mut Oscillator, output: &mut [f32], rate: f32) {
+        match self.modulation {
+            Modulation::HardSync => output.iter_mut().for_each(|tone| {
+                if self.oscillator.advance(rate) {
+                    primary.sync(self.mix);
+                }
+
+                *tone += primary.next(rate);
+            }),
+            Modulation::Ring => output.iter_mut().for_each(|tone| {
+                let modulator = self.oscillator.next(rate);
+
+                *tone += primary.next(rate) * (1.0 - self.mix + self.mix * modulator);
+            }),
+            Modulation::Amplitude => output.iter_mut().for_each(|tone| {
+                let modulator = (self.oscillator.next(rate) + 1.0) * 0.5;
+
+                *tone += primary.next(rate) * (1.0 - self.mix + self.mix * modulator);
+            }),
+        }
+    }
+
+    /// Restart the secondary oscillator from the beginning.
+    pub(crate) fn reset(&mut self) {
+        self.oscillator.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Oscillator;
+    use std::cell::RefCell;
+
+    #[test]
+    fn sync_moves_the_phase_within_the_cycle() {
+        // A cycle is 10 frames
+        let mut oscillator = Oscillator::new(RefCell::new(vec![0.0; 200]), 100, 10);
+
+        oscillator.position = 25.0;
+        oscillator.sync(0.5);
+        assert_eq!(oscillator.position, 22.5);
+
+        oscillator.position = 37.0;
+        oscillator.sync(1.0);
+        assert_eq!(oscillator.position, 30.0);
+
+        oscillator.position = 37.0;
+        oscillator.sync(0.0);
+        assert_eq!(oscillator.position, 37.0);
+    }
+
+    #[test]
+    fn any_rate_wraps_around() {
+        let mut oscillator = Oscillator::new(RefCell::new(vec![0.0; 200]), 100, 10);
+
+        for rate in [250.0, 1e9, -30.0, -1e-9, -250.0, f32::INFINITY, f32::NAN] {
+            oscillator.next(rate);
+            assert!((0.0..100.0).contains(&oscillator.position), "{}", rate);
+        }
     }
 }
@@ -0,0 +1,69 @@
+use crate::{BusId, Mixer, VoiceId};
+
+impl Mixer {
+    /// Freeze a playing sample, it continues where it was when it's resumed.
+    ///
+    /// A paused sample outputs nothing but still counts as a voice.
+    /// Fades are frozen as well, see `pause_bus` to pause a group of samples.
+    ///
+    /// ```rust
+    /// let mut mixer = usfx::Mixer::default();
+    /// let alarm = mixer.play(usfx::Sample::preset_blip(0));
+    ///
+    /// mixer.pause(alarm);
+    /// # let mut buffer = [0.0; 1024];
+    /// // Nothing is heard
+    /// mixer.generate(&mut buffer);
+    ///
+    /// mixer.resume(alarm);
+    /// ```
+    pub fn pause(&mut self, voice: VoiceId) {
+        self.set_paused(voice, true);
+    }
+
+    /// Continue playing a paused sample.
+    pub fn resume(&mut self, voice: VoiceId) {
+        self.set_paused(voice, false);
+    }
+
+    /// Freeze all samples that are playing, the sequencer, the song and the instruments.
+    ///
+    /// Samples played afterwards with `play` are not paused, events sent to instruments are
+    /// handled when they are resumed.
+    pub fn pause_all(&mut self) {
+        self.paused = true;
+        self.generators
+            .iter_mut()
+            .for_each(|generator| generator.paused = true);
+    }
+
+    /// Continue playing all paused samples, the sequencer, the song and the instruments.
+    ///
+    /// Samples on paused buses stay frozen.
+    pub fn resume_all(&mut self) {
+        self.paused = false;
+        self.generators
+            .iter_mut()
+            .for_each(|generator| generator.paused = false);
+    }
+
+    /// Whether a sequencer, song or instrument playing on the bus continues.
+    pub(crate) fn scheduler_running(&self, bus: Option<BusId>) -> bool {
+        !self.paused
+            && bus
+                .filter(|bus| self.contains_bus(*bus))
+                .and_then(|bus| self.buses.get(bus.index))
+                .is_none_or(|bus| bus.running)
+    }
+
+    /// Pause or resume a single generator.
+    fn set_paused(&mut self, voice: VoiceId, paused: bool) {
+        if let Some(generator) = self
+            .generators
+            .iter_mut()
+            .find(|generator| generator.id == voice)
+        {
+            generator.paused = paused;
+        }
+    }
+}
@@ -0,0 +1,222 @@
+use crate::{Generator, Mixer};
+
+/// Time in seconds a stolen voice fades out, so it doesn't click.
+const STEAL_FADE_TIME: f32 = 0.005;
+
+/// Which voice is stopped when a new one is played while the maximum amount of voices is reached.
+///
+/// The stopped voice is quickly faded out so it doesn't click.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum StealPolicy {
+    /// Stop the voice that started first.
+    #[default]
+    Oldest,
+    /// Stop the voice with the lowest volume at the moment, such as a voice near the end of it's
+    /// release.
+    Quietest,
+    /// Stop the oldest voice with the lowest priority, when all voices have a higher priority
+    /// than the new sample it's not played.
+    ///
+    /// See `Sample::priority`.
+    LowestPriority,
+}
+
+impl Mixer {
+    /// Set the maximum amount of voices that play at the same time, unlimited by default.
+    ///
+    /// When a sample is played while the maximum is reached a voice is stopped, see
+    /// [`StealPolicy`].
+    /// The stopped voice fades out in a few milliseconds, during which it doesn't count.
+    /// Every played sample and every note of an instrument is a voice.
+    ///
+    /// ```rust
+    /// let mut mixer = usfx::Mixer::default();
+    /// mixer.max_voices(8);
+    /// mixer.steal_policy(usfx::StealPolicy::Quietest);
+    ///
+    /// // Only 8 bullets are heard at the same time
+    /// for seed in 0..100 {
+    ///     mixer.play(usfx::Sample::preset_laser(seed));
+    /// }
+    /// # let mut buffer = [0.0; 1024];
+    /// mixer.generate(&mut buffer);
+    /// ```
+    ///
+    /// [`StealPolicy`]: enum.StealPolicy.html
+    pub fn max_voices(&mut self, voices: usize) {
+        self.max_voices = Some(voices.max(1));
+    }
+
+    /// Set which voice is stopped when the maximum amount of voices is reached.
+    ///
+    /// This is also used for the polyphony of instruments.
+    pub fn steal_policy(&mut self, policy: StealPolicy) {
+        self.steal_policy = policy;
+    }
+
+    /// Start playing a generator, stealing a voice when there are too many.
+    ///
+    /// The generator is dropped when there's no voice that can be stolen for it.
+    pub(crate) fn add_generator(&mut self, generator: Generator) {
+        if let Some(max_voices) = self.max_voices {
+            if !self.steal(max_voices, generator.priority, |_| true) {
+                return;
+            }
+        }
+
+        self.generators.push(generator);
+    }
+
+    /// Fade out voices matching the filter until there's room for one more with the priority.
+    ///
+    /// Voices that are fading out don't take the room of playing ones, but there can't be more of
+    /// them than the maximum either: the oldest ones are dropped immediately.
+    /// Returns false when there's no room because all voices are more important.
+    pub(crate) fn steal(
+        &mut self,
+        max_voices: usize,
+        priority: i32,
+        filter: impl Fn(&Generator) -> bool,
+    ) -> bool {
+        let fade_frames = (STEAL_FADE_TIME * self.sample_rate as f32) as usize;
+
+        // Voices that are fading out get as many slots as the playing ones, the oldest are
+        // dropped first so the voices stolen below can still fade out
+        let fading = |generator: &Generator| generator.stopping && filter(generator);
+        let mut faded = self.generators.iter().filter(|g| fading(g)).count();
+        while faded >= max_voices {
+            match self.generators.iter().position(&fading) {
+                Some(index) => {
+                    self.generators.remove(index);
+                    faded -= 1;
+                }
+                None => break,
+            }
+        }
+
+        // Voices that are already fading out don't count
+        let candidates = |generator: &&mut Generator| !generator.stopping && filter(generator);
+        let playing = self.generators.iter_mut().filter(candidates).count();
+        let victims = (playing + 1).saturating_sub(max_voices);
+
+        // Only steal when all voices that would be stopped are less or as important, checked
+        // before anything is stopped
+        if self.steal_policy == StealPolicy::LowestPriority
+            && self
+                .generators
+                .iter_mut()
+                .filter(candidates)
+                .filter(|generator| generator.priority <= priority)
+                .count()
+                < victims
+        {
+            return false;
+        }
+
+        for _ in 0..victims {
+            let victim = match self.steal_policy {
+                // The generators are in the order they are started
+                StealPolicy::Oldest => self.generators.iter_mut().find(candidates),
+                StealPolicy::Quietest => self
+                    .generators
+                    .iter_mut()
+                    .filter(candidates)
+                    .min_by(|a, b| a.loudness().total_cmp(&b.loudness())),
+                // The first of the least important ones is the oldest
+                StealPolicy::LowestPriority => self
+                    .generators
+                    .iter_mut()
+                    .filter(candidates)
+                    .min_by_key(|generator| generator.priority),
+            };
+
+            if let Some(victim) = victim {
+                victim.stop(fade_frames);
+            }
+        }
+
+        // Voices that were never heard are stopped immediately
+        self.generators.retain(|generator| !generator.finished);
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Mixer, Sample, StealPolicy};
+
+    #[test]
+    fn max_voices_limits_the_generators() {
+        let mut mixer = Mixer::default();
+        mixer.max_voices(8);
+
+        for seed in 0..1000 {
+            mixer.play(Sample::preset_laser(seed));
+        }
+        assert!(mixer.generators.len() <= 8);
+
+        // Also when the voices are fading out
+        let mut buffer = [0.0; 64];
+        for seed in 0..100 {
+            mixer.play(Sample::preset_laser(seed));
+            mixer.generate(&mut buffer);
+        }
+        assert!(mixer.generators.len() <= 2 * 8);
+    }
+
+    #[test]
+    fn stolen_voice_fades_out() {
+        let mut mixer = Mixer::default();
+        mixer.max_voices(2);
+        assert_eq!(play(&mut mixer, &[0, 1]), vec![0, 1]);
+
+        mixer.play(Sample::default());
+        assert_eq!(mixer.generators.len(), 3);
+        assert!(mixer.generators[0].stopping);
+        assert!(!mixer.generators[1].stopping);
+        assert!(!mixer.generators[2].stopping);
+    }
+
+    /// Play samples with priorities and return the priorities of the voices that are not stopped.
+    fn play(mixer: &mut Mixer, priorities: &[i32]) -> Vec<i32> {
+        for priority in priorities {
+            let mut sample = Sample::default();
+            sample.priority(*priority);
+            mixer.play(sample);
+            // Let them be heard so they fade out when they are stolen
+            let mut buffer = [0.0; 64];
+            mixer.generate(&mut buffer);
+        }
+
+        mixer
+            .generators
+            .iter()
+            .filter(|generator| !generator.stopping)
+            .map(|generator| generator.priority)
+            .collect()
+    }
+
+    #[test]
+    fn priority_only_blocks_lowest_priority_stealing() {
+        for policy in [StealPolicy::Oldest, StealPolicy::Quietest] {
+            let mut mixer = Mixer::default();
+            mixer.max_voices(2);
+            mixer.steal_policy(policy);
+            assert!(play(&mut mixer, &[5, 5, 0]).contains(&0));
+        }
+
+        let mut mixer = Mixer::default();
+        mixer.max_voices(2);
+        mixer.steal_policy(StealPolicy::LowestPriority);
+        assert_eq!(play(&mut mixer, &[5, 1, 0]), vec![5, 1]);
+        assert_eq!(play(&mut mixer, &[2]), vec![5, 2]);
+
+        // Nothing is stopped when the sample can't be played
+        let mut mixer = Mixer::default();
+        mixer.steal_policy(StealPolicy::LowestPriority);
+        play(&mut mixer, &[0, 9, 9]);
+        mixer.max_voices(2);
+        assert_eq!(play(&mut mixer, &[1]), vec![0, 9, 9]);
+    }
+}
@@ -0,0 +1,380 @@
+use crate::{
+    random::Random,
+    validate::{MAX_FREQUENCY, MIN_FREQUENCY},
+    DutyCycle, Modulation, OscillatorType, Sample,
+};
+
+impl Sample {
+    /// Generate a coin or item pickup sound, like the "pickup/coin" button of sfxr.
+    ///
+    /// The same seed always results in the same sound.
+    ///
+    /// ```rust
+    /// let mut mixer = usfx::Mixer::default();
+    ///
+    /// // Play a different coin sound every time
+    /// for seed in 0..3 {
+    ///     mixer.play(usfx::Sample::preset_pickup(seed));
+    /// }
+    /// ```
+    pub fn preset_pickup(seed: u64) -> Self {
+        let mut random = Random::new(seed);
+        let mut sample = Sample::default();
+
+        sample
+            .osc_type(random.pick(&[OscillatorType::Square, OscillatorType::Saw]))
+            .osc_duty_cycle(random.pick(&[DutyCycle::Quarter, DutyCycle::Half]))
+            .osc_frequency(random.range(600.0, 1600.0) as usize)
+            .env_attack(random.range(0.001, 0.01))
+            .env_decay(random.range(0.05, 0.1))
+            .env_sustain(random.range(0.3, 0.6))
+            .env_release(random.range(0.1, 0.3));
+
+        // Most coins have a second higher note
+        if random.chance(0.7) {
+            sample
+                .arp_amount(random.range(1.25, 1.6))
+                .arp_time(random.range(0.04, 0.1));
+        }
+
+        sample
+    }
+
+    /// Generate a shooting sound, like the "laser/shoot" button of sfxr.
+    ///
+    /// The same seed always results in the same sound.
+    pub fn preset_laser(seed: u64) -> Self {
+        let mut random = Random::new(seed);
+        let mut sample = Sample::default();
+
+        sample
+            .osc_type(random.pick(&[
+                OscillatorType::Square,
+                OscillatorType::Saw,
+                OscillatorType::Sine,
+            ]))
+            .osc_duty_cycle(random.pick(&[DutyCycle::Eight, DutyCycle::Quarter, DutyCycle::Half]))
+            .osc_frequency(random.range(800.0, 2000.0) as usize)
+            .env_attack(random.range(0.001, 0.005))
+            .env_decay(random.range(0.02, 0.1))
+            .env_sustain(random.range(0.2, 0.5))
+            .env_release(random.range(0.1, 0.3))
+            // The pitch always drops
+            .arp_amount(random.range(0.4, 0.75))
+            .arp_time(random.range(0.02, 0.08));
+
+        if random.chance(0.3) {
+            sample
+                .mod_mode(Modulation::Ring)
+                .mod_ratio(random.range(0.3, 0.7))
+                .mod_mix(random.range(0.3, 0.8));
+        }
+
+        if random.chance(0.3) {
+            sample
+                .pha_offset(random.range(0.0, 0.3))
+                .pha_sweep(random.range(-0.2, 0.0));
+        }
+
+        sample
+    }
+
+    /// Generate an explosion sound, like the "explosion" button of sfxr.
+    ///
+    /// The same seed always results in the same sound.
+    pub fn preset_explosion(seed: u64) -> Self {
+        let mut random = Random::new(seed);
+        let mut sample = Sample::default();
+
+        sample
+            .osc_type(OscillatorType::Noise)
+            // The frequency is used as the seed of the noise
+            .osc_frequency(random.range(1.0, 20000.0) as usize)
+            .env_attack(random.range(0.001, 0.01))
+            .env_decay(random.range(0.1, 0.3))
+            .env_sustain(random.range(0.4, 0.8))
+            .env_release(random.range(0.3, 0.8))
+            .dis_crunch(random.range(0.0, 0.5))
+            .dis_drive(random.range(0.6, 1.0));
+
+        if random.chance(0.5) {
+            sample
+                .pha_offset(random.range(-0.3, 0.5))
+                .pha_sweep(random.range(-0.3, 0.0));
+        }
+
+        if random.chance(0.3) {
+            sample.repeat_time(random.range(0.1, 0.3));
+        }
+
+        sample
+    }
+
+    /// Generate a powerup sound, like the "powerup" button of sfxr.
+    ///
+    /// The same seed always results in the same sound.
+    pub fn preset_powerup(seed: u64) -> Self {
+        let mut random = Random::new(seed);
+        let mut sample = Sample::default();
+
+        sample
+            .osc_type(random.pick(&[
+                OscillatorType::Square,
+                OscillatorType::Saw,
+                OscillatorType::Triangle,
+            ]))
+            .osc_duty_cycle(random.pick(&[DutyCycle::Quarter, DutyCycle::Third, DutyCycle::Half]))
+            .osc_frequency(random.range(300.0, 800.0) as usize)
+            .env_attack(random.range(0.001, 0.02))
+            .env_decay(random.range(0.1, 0.3))
+            .env_sustain(random.range(0.4, 0.7))
+            .env_release(random.range(0.2, 0.5))
+            // A rising pitch that restarts
+            .arp_amount(random.range(1.3, 2.0))
+            .arp_time(random.range(0.03, 0.06))
+            .repeat_time(random.range(0.07, 0.15));
+
+        if random.chance(0.3) {
+            sample
+                .mod_mode(Modulation::Amplitude)
+                .mod_ratio(random.range(0.01, 0.05))
+                .mod_mix(random.range(0.2, 0.5));
+        }
+
+        sample
+    }
+
+    /// Generate a hit or hurt sound, like the "hit/hurt" button of sfxr.
+    ///
+    /// The same seed always results in the same sound.
+    pub fn preset_hit(seed: u64) -> Self {
+        let mut random = Random::new(seed);
+        let mut sample = Sample::default();
+
+        sample
+            .osc_type(random.pick(&[
+                OscillatorType::Noise,
+                OscillatorType::Saw,
+                OscillatorType::Square,
+            ]))
+            .osc_frequency(random.range(100.0, 400.0) as usize)
+            .env_attack(random.range(0.001, 0.005))
+            .env_decay(random.range(0.02, 0.06))
+            .env_sustain(random.range(0.3, 0.6))
+            .env_release(random.range(0.05, 0.2))
+            // Drop in pitch halfway
+            .arp_amount(random.range(0.5, 0.8))
+            .arp_time(random.range(0.02, 0.05))
+            .dis_drive(random.range(0.5, 1.0));
+
+        if random.chance(0.5) {
+            sample.dis_crunch(random.range(0.2, 0.6));
+        }
+
+        sample
+    }
+
+    /// Generate a jumping sound, like the "jump" button of sfxr.
+    ///
+    /// The same seed always results in the same sound.
+    pub fn preset_jump(seed: u64) -> Self {
+        let mut random = Random::new(seed);
+        let mut sample = Sample::default();
+
+        sample
+            .osc_type(random.pick(&[OscillatorType::Square, OscillatorType::Triangle]))
+            .osc_duty_cycle(random.pick(&[DutyCycle::Eight, DutyCycle::Quarter, DutyCycle::Half]))
+            .osc_frequency(random.range(250.0, 500.0) as usize)
+            .env_attack(random.range(0.001, 0.01))
+            .env_decay(random.range(0.05, 0.15))
+            .env_sustain(random.range(0.3, 0.6))
+            .env_release(random.range(0.1, 0.3))
+            // The pitch goes up quickly
+            .arp_amount(random.range(1.3, 1.8))
+            .arp_time(random.range(0.02, 0.06));
+
+        if random.chance(0.3) {
+            sample
+                .mod_mode(Modulation::HardSync)
+                .mod_ratio(random.range(0.5, 0.9))
+                .mod_mix(1.0);
+        }
+
+        sample
+    }
+
+    /// Generate a short menu selection sound, like the "blip/select" button of sfxr.
+    ///
+    /// The same seed always results in the same sound.
+    pub fn preset_blip(seed: u64) -> Self {
+        let mut random = Random::new(seed);
+        let mut sample = Sample::default();
+
+        sample
+            .osc_type(random.pick(&[OscillatorType::Square, OscillatorType::Sine]))
+            .osc_duty_cycle(random.pick(&[DutyCycle::Quarter, DutyCycle::Half]))
+            .osc_frequency(random.range(400.0, 1200.0) as usize)
+            .env_attack(random.range(0.001, 0.005))
+            .env_decay(random.range(0.01, 0.04))
+            .env_sustain(random.range(0.3, 0.6))
+            .env_release(random.range(0.02, 0.1));
+
+        sample
+    }
+
+    /// Pick random values for all parameters, the result will sound completely different.
+    ///
+    /// The same seed always results in the same sound.
+    /// All values are kept within their documented ranges, the priority and the category are not
+    /// changed.
+    pub fn randomize(&mut self, seed: u64) -> &mut Self {
+        let mut random = Random::new(seed);
+
+        *self = Sample {
+            priority: self.priority,
+            category: self.category,
+            ..Sample::default()
+        };
+
+        // Pick the frequency on an exponential scale so low and high sounds are as likely
+        self.osc_type(random.pick(&OSCILLATOR_TYPES))
+            .osc_duty_cycle(random.pick(&DUTY_CYCLES))
+            .osc_frequency(2.0f32.powf(random.range(5.5, 13.0)) as usize)
+            .env_attack(random.range(0.001, 0.3))
+            .env_decay(random.range(0.01, 0.5))
+            .env_sustain(random.range(0.1, 0.9))
+            .env_release(random.range(0.05, 1.0));
+
+        if random.chance(0.3) {
+            // Keep the secondary oscillator below the highest frequency
+            let max_ratio = self.max_ratio();
+            self.mod_mode(random.pick(&MODULATIONS))
+                .mod_type(random.pick(&OSCILLATOR_TYPES))
+                .mod_ratio(random.range(0.25, 4.0).min(max_ratio))
+                .mod_mix(random.range(0.0, 1.0));
+        }
+
+        if random.chance(0.3) {
+            self.arp_amount(random.range(0.5, 2.0))
+                .arp_time(random.range(0.02, 0.3));
+        }
+
+        if random.chance(0.2) {
+            self.repeat_time(random.range(0.05, 0.5));
+        }
+
+        if random.chance(0.2) {
+            self.pha_offset(random.range(-1.0, 1.0))
+                .pha_sweep(random.range(-0.5, 0.5));
+        }
+
+        if random.chance(0.3) {
+            self.dis_crunch(random.range(0.0, 1.0))
+                .dis_drive(random.range(0.0, 1.0));
+        }
+
+        self
+    }
+
+    /// Nudge all parameters that are set slightly, to create a variation of the same sound.
+    ///
+    /// The amount is the maximum change relative to the range of the parameter, a range from
+    /// 0.0-1.0 will result in proper behavior.
+    /// The same seed always results in the same variation.
+    /// All values are kept within their documented ranges, the wave types are never changed.
+    ///
+    /// ```rust
+    /// let mut mixer = usfx::Mixer::default();
+    ///
+    /// // Play a slightly different footstep every time
+    /// for seed in 0..3 {
+    ///     mixer.play(*usfx::Sample::preset_hit(0).mutate(0.1, seed));
+    /// }
+    /// ```
+    pub fn mutate(&mut self, amount: f32, seed: u64) -> &mut Self {
+        let mut random = Random::new(seed);
+
+        // Move a value randomly within the range
+        let mut nudge = |value: f32, min: f32, max: f32| {
+            (value + random.range(-amount, amount) * (max - min)).clamp(min, max)
+        };
+
+        // The pitch is nudged on an exponential scale so the change is audible everywhere, with
+        // the cents so the frequency keeps using the same lookup table
+        let frequency = self.osc_frequency.max(1) as f32;
+        self.osc_cents = (self.osc_cents + nudge(0.0, -1200.0, 1200.0)).clamp(
+            1200.0 * (MIN_FREQUENCY as f32 / frequency).log2(),
+            1200.0 * (MAX_FREQUENCY as f32 / frequency).log2(),
+        );
+
+        let max_ratio = self.max_ratio();
+        self.volume = self.volume.map(|volume| nudge(volume, 0.0, 1.0));
+        self.mod_ratio = nudge(self.mod_ratio, 0.0, 4.0).clamp(0.01, max_ratio);
+        self.mod_mix = nudge(self.mod_mix, 0.0, 1.0);
+        self.arp_amount = self
+            .arp_amount
+            .map(|arp| nudge(arp, 0.0, 2.0).clamp(0.01, max_ratio));
+        self.arp_time = nudge(self.arp_time, 0.0, 1.0);
+        self.repeat_time = self
+            .repeat_time
+            .map(|repeat| nudge(repeat, 0.0, 1.0).max(0.01));
+        self.env_attack = nudge(self.env_attack, 0.0, 1.0).max(0.001);
+        self.env_decay = nudge(self.env_decay, 0.0, 1.0).max(0.001);
+        self.env_sustain = nudge(self.env_sustain, 0.0, 1.0).max(0.01);
+        self.env_release = nudge(self.env_release, 0.0, 1.0).max(0.001);
+        self.pha_offset = self.pha_offset.map(|offset| nudge(offset, -1.0, 1.0));
+        self.pha_sweep = self.pha_sweep.map(|sweep| nudge(sweep, -1.0, 1.0));
+        self.dis_crunch = self.dis_crunch.map(|crunch| nudge(crunch, 0.0, 1.0));
+        self.dis_drive = self.dis_drive.map(|drive| nudge(drive, 0.0, 1.0));
+
+        self
+    }
+
+    /// The highest multiple of the frequency that stays below the highest frequency.
+    fn max_ratio(&self) -> f32 {
+        MAX_FREQUENCY as f32 / self.tuned_frequency()
+    }
+}
+
+/// All oscillator types that can be picked randomly.
+const OSCILLATOR_TYPES: [OscillatorType; 5] = [
+    OscillatorType::Sine,
+    OscillatorType::Saw,
+    OscillatorType::Triangle,
+    OscillatorType::Square,
+    OscillatorType::Noise,
+];
+
+/// All duty cycles that can be picked randomly.
+const DUTY_CYCLES: [DutyCycle; 4] = [
+    DutyCycle::Eight,
+    DutyCycle::Quarter,
+    DutyCycle::Third,
+    DutyCycle::Half,
+];
+
+/// All modulations that can be picked randomly.
+const MODULATIONS: [Modulation; 3] = [
+    Modulation::HardSync,
+    Modulation::Ring,
+    Modulation::Amplitude,
+];
+
+#[cfg(test)]
+mod tests {
+    use crate::Sample;
+
+    #[test]
+    fn random_samples_are_valid() {
+        for seed in 0..1000 {
+            let mut sample = Sample::default();
+            sample.randomize(seed);
+            assert_eq!(sample.validate(), Ok(()), "randomize {}", seed);
+
+            for mutation in 0..10 {
+                sample.mutate(1.0, mutation);
+                assert_eq!(sample.validate(), Ok(()), "mutate {}", seed);
+            }
+        }
+    }
+}
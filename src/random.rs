@@ -0,0 +1,36 @@
+use randomize::{formulas, PCG32};
+
+/// Deterministic random number generator used for procedurally picking parameters.
+#[derive(Debug, Clone)]
+pub(crate) struct Random {
+    pcg: PCG32,
+}
+
+impl Random {
+    /// Instantiate a new generator, the same seed always results in the same numbers.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            pcg: PCG32::seed(seed, 5),
+        }
+    }
+
+    /// A random number between the minimum (inclusive) and the maximum (exclusive).
+    pub fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + formulas::f32_half_open_right(self.pcg.next_u32()) * (max - min)
+    }
+
+    /// Returns true with the chance between 0.0-1.0.
+    pub fn chance(&mut self, chance: f32) -> bool {
+        self.range(0.0, 1.0) < chance
+    }
+
+    /// A random index for a slice with the length.
+    pub fn index(&mut self, len: usize) -> usize {
+        (self.pcg.next_u32() as usize) % len
+    }
+
+    /// Pick a random item from a slice.
+    pub fn pick<T: Copy>(&mut self, items: &[T]) -> T {
+        items[self.index(items.len())]
+    }
+}
@@ -0,0 +1,158 @@
+use crate::{Category, DutyCycle, Looping, Modulation, OscillatorType, Sample};
+use serde::{Deserialize, Serialize};
+
+/// The version of the serialization format of [`Sample`] written by this version of usfx.
+///
+/// Every serialized sample contains a `version` field with this number.
+/// Samples serialized by older versions are migrated when they are deserialized, parameters that
+/// are missing get their default value and unknown parameters are ignored.
+///
+/// For example a square wave serialized as JSON, all parameters that are left out use the value
+/// of `Sample::default()`:
+///
+/// ```json
+/// { "version": 1, "osc_type": "Square", "osc_frequency": 880 }
+/// ```
+///
+/// [`Sample`]: struct.Sample.html
+pub const SAMPLE_FORMAT_VERSION: u32 = 1;
+
+/// The serialized representation of a sample.
+///
+/// Every field is optional, missing fields get the value of `Sample::default()`.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct SampleSchema {
+    /// Version of the format, samples saved by usfx 0.1.5 and before don't have it and are 0.
+    version: u32,
+    volume: Option<f32>,
+    volume_variance: Option<f32>,
+    pitch_variance: Option<f32>,
+    osc_frequency: usize,
+    osc_cents: f32,
+    osc_type: OscillatorType,
+    osc_duty_cycle: DutyCycle,
+    mod_mode: Option<Modulation>,
+    mod_type: OscillatorType,
+    mod_ratio: f32,
+    mod_mix: f32,
+    arp_amount: Option<f32>,
+    arp_time: f32,
+    repeat_time: Option<f32>,
+    env_attack: f32,
+    env_decay: f32,
+    env_release: f32,
+    env_sustain: f32,
+    looping: Looping,
+    loop_start: f32,
+    loop_end: f32,
+    pha_offset: Option<f32>,
+    pha_sweep: Option<f32>,
+    dis_crunch: Option<f32>,
+    dis_drive: Option<f32>,
+    priority: i32,
+    category: Category,
+}
+
+impl SampleSchema {
+    /// Convert older versions of the format to the current version, one version at a time.
+    ///
+    /// Versions newer than the current one are kept as they are, the fields that are known are
+    /// still used.
+    fn migrate(mut self) -> Self {
+        if self.version == 0 {
+            self = self.migrate_v0();
+        }
+
+        self
+    }
+
+    /// Version 0 is the format written by usfx 0.1.5 and before.
+    ///
+    /// The fields it contains have the same meaning in version 1, the fields added after it are
+    /// already filled with their defaults.
+    fn migrate_v0(self) -> Self {
+        Self { version: 1, ..self }
+    }
+}
+
+impl Default for SampleSchema {
+    /// Use the default sample, a missing version means it's from before versioning was added.
+    fn default() -> Self {
+        Self {
+            version: 0,
+            ..Self::from(Sample::default())
+        }
+    }
+}
+
+impl From<Sample> for SampleSchema {
+    fn from(sample: Sample) -> Self {
+        Self {
+            version: SAMPLE_FORMAT_VERSION,
+            volume: sample.volume,
+            volume_variance: sample.volume_variance,
+            pitch_variance: sample.pitch_variance,
+            osc_frequency: sample.osc_frequency,
+            osc_cents: sample.osc_cents,
+            osc_type: sample.osc_type,
+            osc_duty_cycle: sample.osc_duty_cycle,
+            mod_mode: sample.mod_mode,
+            mod_type: sample.mod_type,
+            mod_ratio: sample.mod_ratio,
+            mod_mix: sample.mod_mix,
+            arp_amount: sample.arp_amount,
+            arp_time: sample.arp_time,
+            repeat_time: sample.repeat_time,
+            env_attack: sample.env_attack,
+            env_decay: sample.env_decay,
+            env_release: sample.env_release,
+            env_sustain: sample.env_sustain,
+            looping: sample.looping,
+            loop_start: sample.loop_start,
+            loop_end: sample.loop_end,
+            pha_offset: sample.pha_offset,
+            pha_sweep: sample.pha_sweep,
+            dis_crunch: sample.dis_crunch,
+            dis_drive: sample.dis_drive,
+            priority: sample.priority,
+            category: sample.category,
+        }
+    }
+}
+
+impl From<SampleSchema> for Sample {
+    fn from(schema: SampleSchema) -> Self {
+        let schema = schema.migrate();
+
+        Self {
+            volume: schema.volume,
+            volume_variance: schema.volume_variance,
+            pitch_variance: schema.pitch_variance,
+            osc_frequency: schema.osc_frequency,
+            osc_cents: schema.osc_cents,
+            osc_type: schema.osc_type,
+            osc_duty_cycle: schema.osc_duty_cycle,
+            mod_mode: schema.mod_mode,
+            mod_type: schema.mod_type,
+            mod_ratio: schema.mod_ratio,
+            mod_mix: schema.mod_mix,
+            arp_amount: schema.arp_amount,
+            arp_time: schema.arp_time,
+            repeat_time: schema.repeat_time,
+            env_attack: schema.env_attack,
+            env_decay: schema.env_decay,
+            env_release: schema.env_release,
+            env_sustain: schema.env_sustain,
+            looping: schema.looping,
+            loop_start: schema.loop_start,
+            loop_end: schema.loop_end,
+            pha_offset: schema.pha_offset,
+            pha_sweep: schema.pha_sweep,
+            dis_crunch: schema.dis_crunch,
+            dis_drive: schema.dis_drive,
+            priority: schema.priority,
+            category: schema.category,
+        }
+    }
+}
@@ -0,0 +1,280 @@
+use crate::{BusId, Note, Sample};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A sample that starts on a step of a pattern.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Copy, Clone)]
+struct Trigger {
+    /// The step of the pattern on which the sample starts.
+    step: usize,
+    /// The sample that's played.
+    sample: Sample,
+    /// The pitch of the sample, when not set the frequency of the sample is used.
+    note: Option<Note>,
+    /// Amount of steps the note is held before it's released.
+    length: Option<f32>,
+}
+
+/// A sequence of steps with samples that start on them, like a pattern of a tracker.
+///
+/// Play patterns with a [`Sequencer`].
+///
+/// [`Sequencer`]: struct.Sequencer.html
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    /// Amount of steps in the pattern.
+    steps: usize,
+    /// The samples started in this pattern, sorted by step.
+    triggers: Vec<Trigger>,
+}
+
+impl Pattern {
+    /// Create an empty pattern with an amount of steps.
+    pub fn new(steps: usize) -> Self {
+        Self {
+            steps,
+            triggers: vec![],
+        }
+    }
+
+    /// Start a sample on a step, steps outside of the pattern are ignored.
+    ///
+    /// When a note is given the sample is played at that pitch.
+    /// When a length is given the sample holds the sustain of the envelope for that amount of
+    /// steps before it's released, otherwise the envelope is released as soon as the sustain
+    /// height is reached.
+    pub fn trigger(
+        &mut self,
+        step: usize,
+        sample: Sample,
+        note: Option<Note>,
+        length: Option<f32>,
+    ) -> &mut Self {
+        if step < self.steps {
+            // Keep the triggers sorted by step, samples on the same step keep the order they are
+            // added in
+            let index = self
+                .triggers
+                .iter()
+                .position(|trigger| trigger.step > step)
+                .unwrap_or(self.triggers.len());
+            self.triggers.insert(
+                index,
+                Trigger {
+                    step,
+                    sample,
+                    note,
+                    length,
+                },
+            );
+        }
+
+        self
+    }
+
+    /// Amount of steps in the pattern.
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+}
+
+/// A sample that should be started by the mixer.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Scheduled {
+    /// The sample to play.
+    pub(crate) sample: Sample,
+    /// Amount of frames into the buffer after which the sample starts.
+    pub(crate) delay: usize,
+    /// Amount of frames after which the note is released.
+    pub(crate) length: Option<usize>,
+    /// The bus the sample is played on.
+    pub(crate) bus: Option<BusId>,
+}
+
+/// Play patterns of samples at a tempo, sample accurate.
+///
+/// The sequencer is advanced by the [`Mixer`] every time it generates sound, so the samples start
+/// at the exact frame of their step instead of at the start of the buffer.
+/// Patterns are played in the order they are added, after the last one the first one is played
+/// again.
+///
+/// ```rust
+/// let mut kick = usfx::Sample::default();
+/// kick.osc_frequency(150).osc_type(usfx::OscillatorType::Triangle);
+///
+/// let mut lead = usfx::Sample::default();
+/// lead.osc_type(usfx::OscillatorType::Square).env_sustain(0.4);
+///
+/// // A pattern of four beats with sixteenth notes
+/// let mut pattern = usfx::Pattern::new(16);
+/// for step in (0..16).step_by(4) {
+///     pattern.trigger(step, kick, None, None);
+/// }
+/// // Hold a C4 for two steps and an E4 for four
+/// pattern.trigger(2, lead, Some("C4".parse().unwrap()), Some(2.0));
+/// pattern.trigger(10, lead, Some("E4".parse().unwrap()), Some(4.0));
+///
+/// let mut sequencer = usfx::Sequencer::new(120.0);
+/// sequencer.swing(0.2).pattern(pattern);
+///
+/// let mut mixer = usfx::Mixer::default();
+/// mixer.load_sequencer(sequencer);
+///
+/// // The samples are played while generating
+/// # let mut buffer = [0.0; 1024];
+/// mixer.generate(&mut buffer);
+/// ```
+///
+/// [`Mixer`]: struct.Mixer.html
+#[derive(Debug, Clone)]
+pub struct Sequencer {
+    /// Beats per minute.
+    bpm: f32,
+    /// Amount of steps in a beat.
+    steps_per_beat: usize,
+    /// Fraction of a step every second step is delayed.
+    swing: f32,
+    /// Whether to start again after the last pattern.
+    looping: bool,
+    /// The patterns in the order they are played.
+    patterns: Vec<Pattern>,
+    /// The bus the samples are played on.
+    pub(crate) bus: Option<BusId>,
+
+    /// Index of the pattern that's playing.
+    pattern: usize,
+    /// The step in the pattern that's played next.
+    step: usize,
+    /// The frame on which the next step starts without swing, relative to the current buffer.
+    next_step: f64,
+}
+
+impl Sequencer {
+    /// Create a sequencer with a tempo in beats per minute and four steps per beat.
+    pub fn new(bpm: f32) -> Self {
+        Self {
+            bpm,
+            steps_per_beat: 4,
+            swing: 0.0,
+            looping: true,
+            patterns: vec![],
+            bus: None,
+            pattern: 0,
+            step: 0,
+            next_step: 0.0,
+        }
+    }
+
+    /// Set the tempo in beats per minute, can be changed while playing.
+    pub fn bpm(&mut self, bpm: f32) -> &mut Self {
+        self.bpm = bpm;
+
+        self
+    }
+
+    /// Set the amount of steps in a beat, 4 means every step is a sixteenth note.
+    pub fn steps_per_beat(&mut self, steps: usize) -> &mut Self {
+        self.steps_per_beat = steps.max(1);
+
+        self
+    }
+
+    /// Delay every second step by this fraction of a step.
+    ///
+    /// A range from 0.0-1.0 will result in proper behavior, 0.33 gives a triplet feel.
+    pub fn swing(&mut self, swing: f32) -> &mut Self {
+        self.swing = swing;
+
+        self
+    }
+
+    /// Set whether the first pattern is played again after the last one, enabled by default.
+    pub fn looping(&mut self, looping: bool) -> &mut Self {
+        self.looping = looping;
+
+        self
+    }
+
+    /// Add a pattern that's played after the previously added patterns.
+    pub fn pattern(&mut self, pattern: Pattern) -> &mut Self {
+        self.patterns.push(pattern);
+
+        self
+    }
+
+    /// Play all samples on a bus of the mixer.
+    pub fn bus(&mut self, bus: BusId) -> &mut Self {
+        self.bus = Some(bus);
+
+        self
+    }
+
+    /// Whether all patterns are played and the sequencer doesn't loop.
+    pub fn finished(&self) -> bool {
+        self.pattern >= self.patterns.len()
+    }
+
+    /// Collect the samples starting in the next amount of frames and move forward in time.
+    pub(crate) fn advance(
+        &mut self,
+        frames: usize,
+        sample_rate: usize,
+        scheduled: &mut Vec<Scheduled>,
+    ) {
+        let step_frames = 60.0 / self.bpm as f64 / self.steps_per_beat as f64 * sample_rate as f64;
+
+        // Steps can't be shorter than a frame otherwise we would never progress
+        if !step_frames.is_finite() || step_frames < 1.0 {
+            return;
+        }
+
+        while !self.finished() {
+            // Every odd step is delayed by the swing
+            let swing = if self.step % 2 == 1 {
+                self.swing as f64 * step_frames
+            } else {
+                0.0
+            };
+            let start = (self.next_step + swing).round().max(0.0) as usize;
+            if start >= frames {
+                break;
+            }
+
+            let pattern = &self.patterns[self.pattern];
+            pattern
+                .triggers
+                .iter()
+                .filter(|trigger| trigger.step == self.step)
+                .for_each(|trigger| {
+                    let mut sample = trigger.sample;
+                    if let Some(note) = trigger.note {
+                        sample.note(note);
+                    }
+
+                    scheduled.push(Scheduled {
+                        sample,
+                        delay: start,
+                        length: trigger
+                            .length
+                            .map(|length| (length.max(0.0) as f64 * step_frames) as usize),
+                        bus: self.bus,
+                    });
+                });
+
+            // Move to the next step, pattern or back to the start
+            self.next_step += step_frames;
+            self.step += 1;
+            if self.step >= pattern.steps {
+                self.step = 0;
+                self.pattern += 1;
+                if self.looping && self.pattern >= self.patterns.len() {
+                    self.pattern = 0;
+                }
+            }
+        }
+
+        self.next_step -= frames as f64;
+    }
+}
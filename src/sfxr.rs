@@ -0,0 +1,773 @@
+use crate::{DutyCycle, OscillatorType, Sample};
+use std::{error::Error, fmt};
+
+/// The sample rate sfxr uses to calculate it's parameters.
+const SFXR_SAMPLE_RATE: f32 = 44_100.0;
+/// The sustain height used to mimic the sustain time of sfxr with the ADSR envelope.
+const SUSTAIN_HEIGHT: f32 = 0.99;
+/// The shortest time of an envelope slope, zero is not allowed.
+const MIN_TIME: f32 = 0.0001;
+/// The alphabet used by the jsfxr share URLs.
+const BASE58_ALPHABET: &str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+/// The order of the parameters in the jsfxr share URLs, the wave type is stored before them.
+const JSFXR_PARAMETERS: [&str; 22] = [
+    "p_env_attack",
+    "p_env_sustain",
+    "p_env_punch",
+    "p_env_decay",
+    "p_base_freq",
+    "p_freq_limit",
+    "p_freq_ramp",
+    "p_freq_dramp",
+    "p_vib_strength",
+    "p_vib_speed",
+    "p_arp_mod",
+    "p_arp_speed",
+    "p_duty",
+    "p_duty_ramp",
+    "p_repeat_speed",
+    "p_pha_offset",
+    "p_pha_ramp",
+    "p_lpf_freq",
+    "p_lpf_ramp",
+    "p_lpf_resonance",
+    "p_hpf_freq",
+    "p_hpf_ramp",
+];
+/// The order of the parameters in the bfxr parameter strings.
+const BFXR_PARAMETERS: [&str; 32] = [
+    "waveType",
+    "masterVolume",
+    "attackTime",
+    "sustainTime",
+    "sustainPunch",
+    "decayTime",
+    "compressionAmount",
+    "startFrequency",
+    "minFrequency",
+    "slide",
+    "deltaSlide",
+    "vibratoDepth",
+    "vibratoSpeed",
+    "overtones",
+    "overtoneFalloff",
+    "changeRepeat",
+    "changeAmount",
+    "changeSpeed",
+    "changeAmount2",
+    "changeSpeed2",
+    "squareDuty",
+    "dutySweep",
+    "repeatSpeed",
+    "flangerOffset",
+    "flangerSweep",
+    "lpFilterCutoff",
+    "lpFilterCutoffSweep",
+    "lpFilterResonance",
+    "hpFilterCutoff",
+    "hpFilterCutoffSweep",
+    "bitCrush",
+    "bitCrushSweep",
+];
+
+/// A sound definition converted from sfxr, jsfxr or bfxr.
+///
+/// ```rust
+/// // A jsfxr sound, as copied from the "serialize" button
+/// let imported = usfx::Sample::from_jsfxr_json(r#"{
+///     "wave_type": 0,
+///     "p_env_attack": 0,
+///     "p_env_sustain": 0.3,
+///     "p_env_decay": 0.4,
+///     "p_base_freq": 0.3,
+///     "p_freq_ramp": 0.2
+/// }"#).unwrap();
+///
+/// // The frequency slide can't be converted
+/// assert_eq!(imported.unsupported[0].name, "p_freq_ramp");
+///
+/// let mut mixer = usfx::Mixer::default();
+/// mixer.play(imported.sample);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Imported {
+    /// The converted sample.
+    pub sample: Sample,
+    /// Parameters that are used in the definition but have no equivalent in usfx.
+    pub unsupported: Vec<Unsupported>,
+}
+
+/// A parameter that has no equivalent in usfx and is therefore ignored.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Unsupported {
+    /// The name of the parameter as used by the source format.
+    pub name: &'static str,
+    /// The value of the parameter.
+    pub value: f32,
+}
+
+/// Error when a sound definition can't be imported.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImportError {
+    /// The data ended before all parameters could be read.
+    UnexpectedEnd,
+    /// The sfxr file has a version which is not 100, 101 or 102.
+    UnsupportedVersion(i32),
+    /// A character in the jsfxr share URL is not valid base58.
+    InvalidBase58(char),
+    /// The jsfxr JSON is not a flat object of numbers.
+    InvalidJson(String),
+    /// A value could not be parsed as a number.
+    InvalidNumber(String),
+    /// The bfxr parameter string doesn't have the expected amount of values.
+    WrongParameterCount {
+        /// The amount of values bfxr stores.
+        expected: usize,
+        /// The amount of values in the string.
+        found: usize,
+    },
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::UnexpectedEnd => write!(f, "unexpected end of sound definition"),
+            ImportError::UnsupportedVersion(version) => {
+                write!(f, "unsupported sfxr file version {}", version)
+            }
+            ImportError::InvalidBase58(character) => {
+                write!(f, "invalid base58 character '{}'", character)
+            }
+            ImportError::InvalidJson(reason) => write!(f, "invalid jsfxr JSON: {}", reason),
+            ImportError::InvalidNumber(value) => write!(f, "invalid number \"{}\"", value),
+            ImportError::WrongParameterCount { expected, found } => write!(
+                f,
+                "expected {} bfxr parameters but found {}",
+                expected, found
+            ),
+        }
+    }
+}
+
+impl Error for ImportError {}
+
+impl Sample {
+    /// Convert a sound saved as a `.sfs` file by the original sfxr.
+    ///
+    /// Versions 100, 101 and 102 of the file format are supported.
+    pub fn from_sfxr(bytes: &[u8]) -> Result<Imported, ImportError> {
+        let mut reader = Reader { bytes };
+
+        let version = reader.i32()?;
+        if !(100..=102).contains(&version) {
+            return Err(ImportError::UnsupportedVersion(version));
+        }
+
+        let mut parameters = Parameters {
+            wave_type: reader.i32()?.max(0) as u32,
+            ..Parameters::default()
+        };
+        if version == 102 {
+            parameters.sound_vol = reader.f32()?;
+        }
+        parameters.base_freq = reader.f32()?;
+        parameters.freq_limit = reader.f32()?;
+        parameters.freq_ramp = reader.f32()?;
+        if version >= 101 {
+            parameters.freq_dramp = reader.f32()?;
+        }
+        parameters.duty = reader.f32()?;
+        parameters.duty_ramp = reader.f32()?;
+        parameters.vib_strength = reader.f32()?;
+        parameters.vib_speed = reader.f32()?;
+        parameters.vib_delay = reader.f32()?;
+        parameters.env_attack = reader.f32()?;
+        parameters.env_sustain = reader.f32()?;
+        parameters.env_decay = reader.f32()?;
+        parameters.env_punch = reader.f32()?;
+        // Whether the filters are enabled, the filter values are checked instead
+        reader.bytes(1)?;
+        parameters.lpf_resonance = reader.f32()?;
+        parameters.lpf_freq = reader.f32()?;
+        parameters.lpf_ramp = reader.f32()?;
+        parameters.hpf_freq = reader.f32()?;
+        parameters.hpf_ramp = reader.f32()?;
+        parameters.pha_offset = reader.f32()?;
+        parameters.pha_ramp = reader.f32()?;
+        parameters.repeat_speed = reader.f32()?;
+        if version >= 101 {
+            parameters.arp_speed = reader.f32()?;
+            parameters.arp_mod = reader.f32()?;
+        }
+
+        Ok(parameters.into_imported())
+    }
+
+    /// Convert a sound serialized as JSON by jsfxr.
+    ///
+    /// Keys that are not sfxr parameters, such as the sample rate of the export, are ignored.
+    pub fn from_jsfxr_json(json: &str) -> Result<Imported, ImportError> {
+        let mut parameters = Parameters::default();
+
+        for (key, value) in parse_flat_json(json)? {
+            parameters.set(&key, value);
+        }
+
+        Ok(parameters.into_imported())
+    }
+
+    /// Convert a sound from a jsfxr share URL, or only the base58 part after the `#` of it.
+    ///
+    /// ```rust
+    /// let imported =
+    ///     usfx::Sample::from_jsfxr_url("https://sfxr.me/#11111111111111111111111111111111");
+    /// // The string is too short to contain a sound
+    /// assert!(imported.is_err());
+    /// ```
+    pub fn from_jsfxr_url(url: &str) -> Result<Imported, ImportError> {
+        let encoded = url.rsplit('#').next().unwrap_or(url).trim();
+        let decoded = decode_base58(encoded)?;
+
+        let mut reader = Reader { bytes: &decoded };
+        let mut parameters = Parameters {
+            wave_type: reader.bytes(1)?[0] as u32,
+            ..Parameters::default()
+        };
+        for name in JSFXR_PARAMETERS.iter() {
+            let value = reader.f32()?;
+            parameters.set(name, value);
+        }
+
+        Ok(parameters.into_imported())
+    }
+
+    /// Convert a sound from a bfxr parameter string, as copied from the bfxr "copy" button.
+    ///
+    /// The string consists of the 32 comma separated values of bfxr in the order of it's
+    /// interface.
+    pub fn from_bfxr(parameters: &str) -> Result<Imported, ImportError> {
+        let values = parameters
+            .trim()
+            .trim_end_matches(',')
+            .split(',')
+            .map(|value| {
+                value
+                    .trim()
+                    .parse::<f32>()
+                    .map_err(|_| ImportError::InvalidNumber(value.trim().to_string()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if values.len() != BFXR_PARAMETERS.len() {
+            return Err(ImportError::WrongParameterCount {
+                expected: BFXR_PARAMETERS.len(),
+                found: values.len(),
+            });
+        }
+
+        let mut result = Parameters {
+            bfxr: true,
+            ..Parameters::default()
+        };
+        for (name, value) in BFXR_PARAMETERS.iter().zip(values) {
+            match *name {
+                "waveType" => result.wave_type = value.max(0.0) as u32,
+                "masterVolume" => result.sound_vol = value,
+                "attackTime" => result.env_attack = value,
+                "sustainTime" => result.env_sustain = value,
+                "sustainPunch" => result.env_punch = value,
+                "decayTime" => result.env_decay = value,
+                "startFrequency" => result.base_freq = value,
+                "minFrequency" => result.freq_limit = value,
+                "slide" => result.freq_ramp = value,
+                "deltaSlide" => result.freq_dramp = value,
+                "vibratoDepth" => result.vib_strength = value,
+                "vibratoSpeed" => result.vib_speed = value,
+                "changeAmount" => result.arp_mod = value,
+                "changeSpeed" => result.arp_speed = value,
+                "squareDuty" => result.duty = value,
+                "dutySweep" => result.duty_ramp = value,
+                "repeatSpeed" => result.repeat_speed = value,
+                "flangerOffset" => result.pha_offset = value,
+                "flangerSweep" => result.pha_ramp = value,
+                "lpFilterCutoff" => result.lpf_freq = value,
+                "lpFilterCutoffSweep" => result.lpf_ramp = value,
+                "lpFilterResonance" => result.lpf_resonance = value,
+                "hpFilterCutoff" => result.hpf_freq = value,
+                "hpFilterCutoffSweep" => result.hpf_ramp = value,
+                // The parameters bfxr added to sfxr, they don't do anything when zero
+                name => {
+                    if value != 0.0 {
+                        result.unsupported.push(Unsupported { name, value });
+                    }
+                }
+            }
+        }
+
+        Ok(result.into_imported())
+    }
+}
+
+/// The parameters of a sound as sfxr defines them.
+#[derive(Debug)]
+struct Parameters {
+    wave_type: u32,
+    sound_vol: f32,
+    base_freq: f32,
+    freq_limit: f32,
+    freq_ramp: f32,
+    freq_dramp: f32,
+    duty: f32,
+    duty_ramp: f32,
+    vib_strength: f32,
+    vib_speed: f32,
+    vib_delay: f32,
+    env_attack: f32,
+    env_sustain: f32,
+    env_decay: f32,
+    env_punch: f32,
+    lpf_resonance: f32,
+    lpf_freq: f32,
+    lpf_ramp: f32,
+    hpf_freq: f32,
+    hpf_ramp: f32,
+    pha_offset: f32,
+    pha_ramp: f32,
+    repeat_speed: f32,
+    arp_speed: f32,
+    arp_mod: f32,
+
+    /// Parameters of the source format that are not part of sfxr.
+    unsupported: Vec<Unsupported>,
+    /// Whether the unsupported parameters should be reported with their bfxr names.
+    bfxr: bool,
+}
+
+impl Default for Parameters {
+    /// The defaults of sfxr.
+    fn default() -> Self {
+        Self {
+            wave_type: 0,
+            sound_vol: 0.5,
+            base_freq: 0.3,
+            freq_limit: 0.0,
+            freq_ramp: 0.0,
+            freq_dramp: 0.0,
+            duty: 0.0,
+            duty_ramp: 0.0,
+            vib_strength: 0.0,
+            vib_speed: 0.0,
+            vib_delay: 0.0,
+            env_attack: 0.0,
+            env_sustain: 0.3,
+            env_decay: 0.4,
+            env_punch: 0.0,
+            lpf_resonance: 0.0,
+            lpf_freq: 1.0,
+            lpf_ramp: 0.0,
+            hpf_freq: 0.0,
+            hpf_ramp: 0.0,
+            pha_offset: 0.0,
+            pha_ramp: 0.0,
+            repeat_speed: 0.0,
+            arp_speed: 0.0,
+            arp_mod: 0.0,
+            unsupported: vec![],
+            bfxr: false,
+        }
+    }
+}
+
+impl Parameters {
+    /// Set a parameter by it's jsfxr name, unknown names are ignored.
+    fn set(&mut self, name: &str, value: f32) {
+        match name {
+            "wave_type" => self.wave_type = value.max(0.0) as u32,
+            "sound_vol" => self.sound_vol = value,
+            "p_base_freq" => self.base_freq = value,
+            "p_freq_limit" => self.freq_limit = value,
+            "p_freq_ramp" => self.freq_ramp = value,
+            "p_freq_dramp" => self.freq_dramp = value,
+            "p_duty" => self.duty = value,
+            "p_duty_ramp" => self.duty_ramp = value,
+            "p_vib_strength" => self.vib_strength = value,
+            "p_vib_speed" => self.vib_speed = value,
+            "p_vib_delay" => self.vib_delay = value,
+            "p_env_attack" => self.env_attack = value,
+            "p_env_sustain" => self.env_sustain = value,
+            "p_env_decay" => self.env_decay = value,
+            "p_env_punch" => self.env_punch = value,
+            "p_lpf_resonance" => self.lpf_resonance = value,
+            "p_lpf_freq" => self.lpf_freq = value,
+            "p_lpf_ramp" => self.lpf_ramp = value,
+            "p_hpf_freq" => self.hpf_freq = value,
+            "p_hpf_ramp" => self.hpf_ramp = value,
+            "p_pha_offset" => self.pha_offset = value,
+            "p_pha_ramp" => self.pha_ramp = value,
+            "p_repeat_speed" => self.repeat_speed = value,
+            "p_arp_speed" => self.arp_speed = value,
+            "p_arp_mod" => self.arp_mod = value,
+            _ => (),
+        }
+    }
+
+    /// Convert the parameters to a sample with the same formulas sfxr uses.
+    fn into_imported(mut self) -> Imported {
+        let mut sample = Sample::default();
+
+        sample.volume(self.sound_vol * 2.0);
+
+        sample.osc_type(match self.wave_type {
+            0 => OscillatorType::Square,
+            1 => OscillatorType::Saw,
+            2 => OscillatorType::Sine,
+            3 => OscillatorType::Noise,
+            4 => OscillatorType::Triangle,
+            wave_type => {
+                self.unsupported.push(Unsupported {
+                    name: if self.bfxr { "waveType" } else { "wave_type" },
+                    value: wave_type as f32,
+                });
+
+                OscillatorType::Square
+            }
+        });
+
+        // sfxr counts the period in 8 times supersampled frames
+        let frequency = 8.0 * SFXR_SAMPLE_RATE * (self.base_freq.powi(2) + 0.001) / 100.0;
+        sample.osc_frequency(frequency.round().max(1.0) as usize);
+
+        // Pick the closest duty cycle
+        let duty = 0.5 - self.duty * 0.5;
+        sample.osc_duty_cycle(
+            *[
+                DutyCycle::Eight,
+                DutyCycle::Quarter,
+                DutyCycle::Third,
+                DutyCycle::Half,
+            ]
+            .iter()
+            .min_by(|a, b| {
+                (a.to_frac() - duty)
+                    .abs()
+                    .partial_cmp(&(b.to_frac() - duty).abs())
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .unwrap_or(&DutyCycle::Half),
+        );
+
+        // sfxr holds the sustain at full height, mimic it by slowly decaying to just below that
+        let attack = sfxr_time(self.env_attack);
+        let sustain = sfxr_time(self.env_sustain);
+        let decay = sfxr_time(self.env_decay);
+        sample.env_attack(attack.max(MIN_TIME));
+        sample.env_sustain(SUSTAIN_HEIGHT);
+        sample.env_decay(sustain.max(MIN_TIME) / ((1.0 - SUSTAIN_HEIGHT) * SUSTAIN_HEIGHT));
+        sample.env_release(decay.max(MIN_TIME) / SUSTAIN_HEIGHT.powi(2));
+
+        if self.arp_mod != 0.0 && self.arp_speed < 1.0 {
+            let multiplier = if self.arp_mod >= 0.0 {
+                1.0 - self.arp_mod.powi(2) * 0.9
+            } else {
+                1.0 + self.arp_mod.powi(2) * 10.0
+            };
+            // sfxr multiplies the period, so the frequency is divided
+            sample.arp_amount(1.0 / multiplier);
+            sample.arp_time(sfxr_limit(self.arp_speed));
+        }
+
+        if self.repeat_speed != 0.0 {
+            sample.repeat_time(sfxr_limit(self.repeat_speed));
+        }
+
+        if self.pha_offset != 0.0 || self.pha_ramp != 0.0 {
+            sample.pha_offset(self.pha_offset);
+            sample.pha_sweep(self.pha_ramp);
+        }
+
+        // Report all parameters that do something in sfxr but can't be converted
+        let bfxr = self.bfxr;
+        let mut unsupported = self.unsupported;
+        unsupported.extend(
+            [
+                ("p_env_punch", "sustainPunch", self.env_punch, 0.0),
+                ("p_freq_limit", "minFrequency", self.freq_limit, 0.0),
+                ("p_freq_ramp", "slide", self.freq_ramp, 0.0),
+                ("p_freq_dramp", "deltaSlide", self.freq_dramp, 0.0),
+                ("p_duty_ramp", "dutySweep", self.duty_ramp, 0.0),
+                ("p_vib_strength", "vibratoDepth", self.vib_strength, 0.0),
+                ("p_lpf_freq", "lpFilterCutoff", self.lpf_freq, 1.0),
+                ("p_lpf_ramp", "lpFilterCutoffSweep", self.lpf_ramp, 0.0),
+                ("p_hpf_freq", "hpFilterCutoff", self.hpf_freq, 0.0),
+                ("p_hpf_ramp", "hpFilterCutoffSweep", self.hpf_ramp, 0.0),
+            ]
+            .iter()
+            .filter(|(_, _, value, neutral)| value != neutral)
+            .map(|&(sfxr_name, bfxr_name, value, _)| Unsupported {
+                name: if bfxr { bfxr_name } else { sfxr_name },
+                value,
+            }),
+        );
+
+        // The resonance always changes the sound when the low-pass filter is active
+        if self.lpf_freq != 1.0 || self.lpf_ramp != 0.0 {
+            unsupported.push(Unsupported {
+                name: if bfxr {
+                    "lpFilterResonance"
+                } else {
+                    "p_lpf_resonance"
+                },
+                value: self.lpf_resonance,
+            });
+        }
+
+        // The frequency of noise is used as the seed so it's pitch is lost
+        if self.wave_type == 3 {
+            unsupported.push(Unsupported {
+                name: if bfxr {
+                    "startFrequency"
+                } else {
+                    "p_base_freq"
+                },
+                value: self.base_freq,
+            });
+        }
+
+        Imported {
+            sample,
+            unsupported,
+        }
+    }
+}
+
+/// Convert an sfxr envelope parameter to seconds.
+fn sfxr_time(parameter: f32) -> f32 {
+    parameter.powi(2) * 100_000.0 / SFXR_SAMPLE_RATE
+}
+
+/// Convert an sfxr speed parameter of the pitch jump and the repeat to seconds.
+fn sfxr_limit(parameter: f32) -> f32 {
+    ((1.0 - parameter).powi(2) * 20_000.0 + 32.0) / SFXR_SAMPLE_RATE
+}
+
+/// Read little endian values from a byte slice.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    /// Take the next amount of bytes.
+    fn bytes(&mut self, amount: usize) -> Result<&'a [u8], ImportError> {
+        if self.bytes.len() < amount {
+            return Err(ImportError::UnexpectedEnd);
+        }
+
+        let (taken, rest) = self.bytes.split_at(amount);
+        self.bytes = rest;
+
+        Ok(taken)
+    }
+
+    /// Read a 32 bits integer.
+    fn i32(&mut self) -> Result<i32, ImportError> {
+        let mut buffer = [0; 4];
+        buffer.copy_from_slice(self.bytes(4)?);
+
+        Ok(i32::from_le_bytes(buffer))
+    }
+
+    /// Read a 32 bits float.
+    fn f32(&mut self) -> Result<f32, ImportError> {
+        let mut buffer = [0; 4];
+        buffer.copy_from_slice(self.bytes(4)?);
+
+        Ok(f32::from_le_bytes(buffer))
+    }
+}
+
+/// Decode a base58 string the same way jsfxr does.
+fn decode_base58(encoded: &str) -> Result<Vec<u8>, ImportError> {
+    let mut leading_zeros = 0;
+    // The decoded number with the least significant byte first
+    let mut number: Vec<u8> = vec![];
+
+    for (index, character) in encoded.chars().enumerate() {
+        let mut carry = BASE58_ALPHABET
+            .find(character)
+            .ok_or(ImportError::InvalidBase58(character))? as u32;
+
+        // Every leading '1' is a zero byte
+        if carry == 0 && leading_zeros == index {
+            leading_zeros += 1;
+        }
+
+        number.iter_mut().for_each(|byte| {
+            carry += *byte as u32 * 58;
+            *byte = (carry & 0xFF) as u8;
+            carry >>= 8;
+        });
+        while carry > 0 {
+            number.push((carry & 0xFF) as u8);
+            carry >>= 8;
+        }
+    }
+
+    Ok(std::iter::repeat_n(0, leading_zeros)
+        .chain(number.into_iter().rev())
+        .collect())
+}
+
+/// Parse a JSON object where all values are numbers, booleans, strings or null.
+///
+/// Booleans are converted to 0.0 or 1.0, strings and null are skipped.
+fn parse_flat_json(json: &str) -> Result<Vec<(String, f32)>, ImportError> {
+    let mut chars = json.trim().chars().peekable();
+    let mut values = vec![];
+
+    let invalid = |reason: &str| ImportError::InvalidJson(reason.to_string());
+
+    // Skip the whitespace and return the next character without consuming it
+    fn peek(chars: &mut std::iter::Peekable<std::str::Chars>) -> Option<char> {
+        while chars.peek().is_some_and(|c| c.is_whitespace()) {
+            chars.next();
+        }
+
+        chars.peek().copied()
+    }
+
+    // Read a string, the opening quote should already be consumed
+    fn string(chars: &mut std::iter::Peekable<std::str::Chars>) -> Result<String, ImportError> {
+        let mut result = String::new();
+        loop {
+            match chars.next() {
+                Some('"') => return Ok(result),
+                Some('\\') => {
+                    chars.next();
+                }
+                Some(character) => result.push(character),
+                None => return Err(ImportError::InvalidJson("unterminated string".to_string())),
+            }
+        }
+    }
+
+    if chars.next() != Some('{') {
+        return Err(invalid("expected an object"));
+    }
+
+    loop {
+        match peek(&mut chars) {
+            Some('}') => break,
+            Some('"') => {
+                chars.next();
+            }
+            _ => return Err(invalid("expected a key")),
+        }
+        let key = string(&mut chars)?;
+
+        if peek(&mut chars) != Some(':') {
+            return Err(invalid("expected a ':'"));
+        }
+        chars.next();
+
+        match peek(&mut chars) {
+            Some('"') => {
+                chars.next();
+                string(&mut chars)?;
+            }
+            Some('{') | Some('[') => return Err(invalid("nested values are not supported")),
+            Some(_) => {
+                let mut literal = String::new();
+                while let Some(&character) = chars.peek() {
+                    if character == ',' || character == '}' || character.is_whitespace() {
+                        break;
+                    }
+                    literal.push(character);
+                    chars.next();
+                }
+
+                match literal.as_str() {
+                    "true" => values.push((key, 1.0)),
+                    "false" => values.push((key, 0.0)),
+                    "null" => (),
+                    number => values.push((
+                        key,
+                        number
+                            .parse()
+                            .map_err(|_| ImportError::InvalidNumber(number.to_string()))?,
+                    )),
+                }
+            }
+            None => return Err(invalid("unexpected end")),
+        }
+
+        match peek(&mut chars) {
+            Some(',') => {
+                chars.next();
+            }
+            Some('}') => break,
+            _ => return Err(invalid("expected a ',' or '}'")),
+        }
+    }
+
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ImportError, Sample};
+
+    /// An `.sfs` file in the layout of version 102 with every float parameter set to a value.
+    fn sfs(version: i32, wave_type: i32, value: f32) -> Vec<u8> {
+        let mut bytes = version.to_le_bytes().to_vec();
+        bytes.extend_from_slice(&wave_type.to_le_bytes());
+        // Volume and the frequency parameters
+        (0..14).for_each(|_| bytes.extend_from_slice(&value.to_le_bytes()));
+        // Whether the filters are enabled
+        bytes.push(1);
+        // The filter, phaser, repeat and arpeggio parameters
+        (0..10).for_each(|_| bytes.extend_from_slice(&value.to_le_bytes()));
+
+        bytes
+    }
+
+    #[test]
+    fn sfs_file() {
+        let imported = Sample::from_sfxr(&sfs(102, 0, 0.5)).unwrap();
+        assert!(imported
+            .unsupported
+            .iter()
+            .any(|unsupported| unsupported.name == "p_lpf_resonance"));
+    }
+
+    #[test]
+    fn sfs_noise_frequency() {
+        let imported = Sample::from_sfxr(&sfs(102, 3, 0.5)).unwrap();
+        assert!(imported
+            .unsupported
+            .iter()
+            .any(|unsupported| unsupported.name == "p_base_freq"));
+    }
+
+    #[test]
+    fn sfs_truncated() {
+        let bytes = sfs(102, 0, 0.5);
+        for length in [0, 3, 8, 57, bytes.len() - 1] {
+            assert_eq!(
+                Sample::from_sfxr(&bytes[..length]).err(),
+                Some(ImportError::UnexpectedEnd)
+            );
+        }
+    }
+
+    #[test]
+    fn sfs_unknown_version() {
+        assert_eq!(
+            Sample::from_sfxr(&sfs(103, 0, 0.5)).err(),
+            Some(ImportError::UnsupportedVersion(103))
+        );
+        assert_eq!(
+            Sample::from_sfxr(&sfs(99, 0, 0.5)).err(),
+            Some(ImportError::UnsupportedVersion(99))
+        );
+    }
+}
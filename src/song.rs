@@ -0,0 +1,173 @@
+use crate::{sequencer::Scheduled, BusId, Note, Sample};
+use std::{collections::HashMap, time::Duration};
+
+/// A note of a song with it's timing in seconds.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct SongNote {
+    /// Time in seconds from the start of the song.
+    pub(crate) start: f64,
+    /// Time in seconds the note is held.
+    pub(crate) length: f64,
+    /// The channel the note is played on.
+    pub(crate) channel: u8,
+    /// The program of the channel when the note started.
+    pub(crate) program: u8,
+    /// The pitch.
+    pub(crate) note: Note,
+    /// Multiplier of the volume, from 0.0-1.0.
+    pub(crate) velocity: f32,
+}
+
+/// Notes with their timing that are played with samples as patches, such as a MIDI file.
+///
+/// Every note is played with the patch of it's channel, when the channel has no patch the patch of
+/// it's program is used and otherwise the default patch.
+/// The patches are played at the pitch of the note, hold the sustain of their envelope until the
+/// note ends and their volume is scaled by the velocity of the note.
+///
+/// Play a song with [`Mixer::play_song`], the notes are started at their exact frame while
+/// generating.
+///
+/// [`Mixer::play_song`]: struct.Mixer.html#method.play_song
+#[derive(Debug, Clone)]
+pub struct Song {
+    /// All notes sorted by their start.
+    notes: Vec<SongNote>,
+    /// The length of the song in seconds.
+    duration: f64,
+    /// Patches for channels.
+    channels: HashMap<u8, Sample>,
+    /// Patches for programs.
+    programs: HashMap<u8, Sample>,
+    /// Patch used when there's no patch for the channel or the program.
+    default_patch: Sample,
+    /// Whether to start again after the end.
+    looping: bool,
+    /// The bus the notes are played on.
+    pub(crate) bus: Option<BusId>,
+
+    /// Index of the next note to start.
+    next: usize,
+    /// Time in seconds of the start of the next buffer.
+    position: f64,
+}
+
+impl Song {
+    /// Create a song from notes, they are sorted by their start.
+    pub(crate) fn new(mut notes: Vec<SongNote>, duration: f64) -> Self {
+        notes.sort_by(|a, b| a.start.total_cmp(&b.start));
+
+        Self {
+            notes,
+            duration,
+            channels: HashMap::new(),
+            programs: HashMap::new(),
+            default_patch: Sample::default(),
+            looping: false,
+            bus: None,
+            next: 0,
+            position: 0.0,
+        }
+    }
+
+    /// Play all notes of a channel with this sample.
+    pub fn channel(&mut self, channel: u8, patch: Sample) -> &mut Self {
+        self.channels.insert(channel, patch);
+
+        self
+    }
+
+    /// Play all notes of a program, such as a General MIDI instrument, with this sample.
+    pub fn program(&mut self, program: u8, patch: Sample) -> &mut Self {
+        self.programs.insert(program, patch);
+
+        self
+    }
+
+    /// Play notes that have no patch for their channel or program with this sample.
+    ///
+    /// When not set a default sample is used.
+    pub fn default_patch(&mut self, patch: Sample) -> &mut Self {
+        self.default_patch = patch;
+
+        self
+    }
+
+    /// Set whether the song starts again after the end, disabled by default.
+    pub fn looping(&mut self, looping: bool) -> &mut Self {
+        self.looping = looping;
+
+        self
+    }
+
+    /// Play all notes on a bus of the mixer.
+    pub fn bus(&mut self, bus: BusId) -> &mut Self {
+        self.bus = Some(bus);
+
+        self
+    }
+
+    /// The length of the song.
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs_f64(self.duration)
+    }
+
+    /// Whether all notes are started and the song doesn't loop.
+    pub fn finished(&self) -> bool {
+        !self.looping && self.next >= self.notes.len()
+    }
+
+    /// Collect the notes starting in the next amount of frames and move forward in time.
+    pub(crate) fn advance(
+        &mut self,
+        frames: usize,
+        sample_rate: usize,
+        scheduled: &mut Vec<Scheduled>,
+    ) {
+        let sample_rate = sample_rate as f64;
+        let mut end = self.position + frames as f64 / sample_rate;
+
+        loop {
+            while let Some(song_note) = self.notes.get(self.next) {
+                if song_note.start >= end {
+                    break;
+                }
+
+                let mut sample = *self
+                    .channels
+                    .get(&song_note.channel)
+                    .or_else(|| self.programs.get(&song_note.program))
+                    .unwrap_or(&self.default_patch);
+                sample.note(song_note.note);
+                sample.volume = Some(sample.volume.unwrap_or(1.0) * song_note.velocity);
+
+                scheduled.push(Scheduled {
+                    sample,
+                    delay: ((song_note.start - self.position) * sample_rate)
+                        .round()
+                        .max(0.0) as usize,
+                    length: Some((song_note.length * sample_rate).round() as usize),
+                    bus: self.bus,
+                });
+
+                self.next += 1;
+            }
+
+            // Start again when the end of the song falls in this buffer, songs shorter than a
+            // frame would never progress
+            if self.looping
+                && self.next >= self.notes.len()
+                && self.duration * sample_rate >= 1.0
+                && self.duration <= end
+            {
+                self.next = 0;
+                self.position -= self.duration;
+                end -= self.duration;
+            } else {
+                break;
+            }
+        }
+
+        self.position = end;
+    }
+}
@@ -0,0 +1,277 @@
+use crate::{OscillatorType, Sample};
+use std::{error::Error, fmt};
+
+/// The sample rate the frequency is checked against with `Sample::validate`.
+const DEFAULT_SAMPLE_RATE: usize = 44_100;
+/// The lowest frequency of the oscillator that's allowed.
+pub(crate) const MIN_FREQUENCY: usize = 1;
+/// The highest frequency of the oscillator that's allowed.
+pub(crate) const MAX_FREQUENCY: usize = 20_000;
+
+/// Error when a sample contains a parameter that will result in broken sound.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SampleError {
+    /// The frequency of the oscillator is outside of the 1-20000 hertz range.
+    FrequencyOutOfRange(usize),
+    /// A frequency is higher than half of the sample rate, so it can't be reproduced.
+    AboveNyquist {
+        /// The name of the parameter that results in the frequency.
+        parameter: &'static str,
+        /// The frequency in hertz.
+        frequency: usize,
+        /// The sample rate the frequency is checked against.
+        sample_rate: usize,
+    },
+    /// A parameter is infinite or not a number.
+    NotFinite {
+        /// The name of the parameter.
+        parameter: &'static str,
+        /// The value of the parameter.
+        value: f32,
+    },
+    /// A parameter must be bigger than zero.
+    NotPositive {
+        /// The name of the parameter.
+        parameter: &'static str,
+        /// The value of the parameter.
+        value: f32,
+    },
+    /// A parameter can't be smaller than zero.
+    Negative {
+        /// The name of the parameter.
+        parameter: &'static str,
+        /// The value of the parameter.
+        value: f32,
+    },
+}
+
+impl fmt::Display for SampleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SampleError::FrequencyOutOfRange(frequency) => write!(
+                f,
+                "osc_frequency must be between {} and {} hertz but is {}",
+                MIN_FREQUENCY, MAX_FREQUENCY, frequency
+            ),
+            SampleError::AboveNyquist {
+                parameter,
+                frequency,
+                sample_rate,
+            } => write!(
+                f,
+                "{} results in a frequency of {} hertz, which is higher than half of the sample \
+                 rate {}",
+                parameter, frequency, sample_rate
+            ),
+            SampleError::NotFinite { parameter, value } => {
+                write!(f, "{} must be a finite number but is {}", parameter, value)
+            }
+            SampleError::NotPositive { parameter, value } => {
+                write!(f, "{} must be bigger than zero but is {}", parameter, value)
+            }
+            SampleError::Negative { parameter, value } => {
+                write!(f, "{} can't be negative but is {}", parameter, value)
+            }
+        }
+    }
+}
+
+impl Error for SampleError {}
+
+impl Sample {
+    /// Check whether all parameters result in proper sound with a sample rate of 44100.
+    ///
+    /// Values outside of the documented ranges that still work are allowed, values that result in
+    /// broken sound are not, such as an attack of zero or a frequency of zero.
+    /// This can be used to check samples loaded from files.
+    ///
+    /// ```rust
+    /// let mut sample = usfx::Sample::default();
+    /// assert!(sample.validate().is_ok());
+    ///
+    /// // An attack of zero would result in a division by zero
+    /// sample.env_attack(0.0);
+    /// assert_eq!(
+    ///     sample.validate(),
+    ///     Err(usfx::SampleError::NotPositive {
+    ///         parameter: "env_attack",
+    ///         value: 0.0
+    ///     })
+    /// );
+    /// ```
+    pub fn validate(&self) -> Result<(), SampleError> {
+        self.validate_for(DEFAULT_SAMPLE_RATE)
+    }
+
+    /// Check whether all parameters result in proper sound with the sample rate.
+    ///
+    /// This is the same as `validate` but checks the frequencies against the sample rate the
+    /// [`Mixer`] uses.
+    ///
+    /// [`Mixer`]: struct.Mixer.html
+    pub fn validate_for(&self, sample_rate: usize) -> Result<(), SampleError> {
+        // All floating point parameters must be real numbers
+        [
+            ("volume", self.volume),
+            ("volume_variance", self.volume_variance),
+            ("pitch_variance", self.pitch_variance),
+            ("osc_cents", Some(self.osc_cents)),
+            ("mod_ratio", Some(self.mod_ratio)),
+            ("mod_mix", Some(self.mod_mix)),
+            ("arp_amount", self.arp_amount),
+            ("arp_time", Some(self.arp_time)),
+            ("repeat_time", self.repeat_time),
+            ("env_attack", Some(self.env_attack)),
+            ("env_decay", Some(self.env_decay)),
+            ("env_sustain", Some(self.env_sustain)),
+            ("env_release", Some(self.env_release)),
+            ("loop_start", Some(self.loop_start)),
+            ("loop_end", Some(self.loop_end)),
+            ("pha_offset", self.pha_offset),
+            ("pha_sweep", self.pha_sweep),
+            ("dis_crunch", self.dis_crunch),
+            ("dis_drive", self.dis_drive),
+        ]
+        .iter()
+        .filter_map(|(parameter, value)| value.map(|value| (*parameter, value)))
+        .try_for_each(|(parameter, value)| {
+            if value.is_finite() {
+                Ok(())
+            } else {
+                Err(SampleError::NotFinite { parameter, value })
+            }
+        })?;
+
+        // The frequency of noise is the seed so it can be anything
+        if self.osc_type != OscillatorType::Noise {
+            if !(MIN_FREQUENCY..=MAX_FREQUENCY).contains(&self.osc_frequency) {
+                return Err(SampleError::FrequencyOutOfRange(self.osc_frequency));
+            }
+            check_nyquist("osc_frequency", self.osc_frequency as f32, sample_rate)?;
+            check_nyquist("osc_cents", self.tuned_frequency(), sample_rate)?;
+        }
+
+        // Values that are divided by or that never let the sound progress
+        [
+            ("env_attack", Some(self.env_attack)),
+            ("env_sustain", Some(self.env_sustain)),
+            ("arp_amount", self.arp_amount),
+            ("repeat_time", self.repeat_time),
+            ("mod_ratio", self.mod_mode.map(|_| self.mod_ratio)),
+        ]
+        .iter()
+        .filter_map(|(parameter, value)| value.map(|value| (*parameter, value)))
+        .try_for_each(|(parameter, value)| {
+            if value > 0.0 {
+                Ok(())
+            } else {
+                Err(SampleError::NotPositive { parameter, value })
+            }
+        })?;
+
+        // Times can be zero but not negative, the envelope skips a decay or release of zero
+        [
+            ("env_decay", self.env_decay),
+            ("env_release", self.env_release),
+            ("arp_time", self.arp_time),
+            ("loop_start", self.loop_start),
+            ("loop_end", self.loop_end),
+        ]
+        .iter()
+        .try_for_each(|&(parameter, value)| {
+            if value >= 0.0 {
+                Ok(())
+            } else {
+                Err(SampleError::Negative { parameter, value })
+            }
+        })?;
+
+        // The arpeggio jumps the pitch up or down
+        if let Some(amount) = self
+            .arp_amount
+            .filter(|_| self.osc_type != OscillatorType::Noise)
+        {
+            check_nyquist("arp_amount", self.tuned_frequency() * amount, sample_rate)?;
+        }
+
+        // The secondary oscillator runs at a multiple of the frequency
+        if self.mod_mode.is_some() && self.mod_type != OscillatorType::Noise {
+            check_nyquist(
+                "mod_ratio",
+                self.tuned_frequency() * self.mod_ratio,
+                sample_rate,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// The frequency of the oscillator including the cents it's tuned with.
+    pub(crate) fn tuned_frequency(&self) -> f32 {
+        self.osc_frequency as f32 * 2.0f32.powf(self.osc_cents / 1200.0)
+    }
+}
+
+/// Check whether the frequency can be reproduced at the sample rate.
+fn check_nyquist(
+    parameter: &'static str,
+    frequency: f32,
+    sample_rate: usize,
+) -> Result<(), SampleError> {
+    if frequency > sample_rate as f32 / 2.0 {
+        Err(SampleError::AboveNyquist {
+            parameter,
+            frequency: frequency.round() as usize,
+            sample_rate,
+        })
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Mixer, Sample, SampleError};
+
+    #[test]
+    fn zero_times_are_valid_and_finite() {
+        let mut sample = Sample::default();
+        sample.env_decay(0.0).env_release(0.0);
+        assert_eq!(sample.validate(), Ok(()));
+
+        let mut mixer = Mixer::default();
+        let voice = mixer.play(sample);
+        let mut buffer = [0.0; 1024];
+        mixer.generate(&mut buffer);
+        assert!(buffer.iter().all(|tone| tone.is_finite()));
+
+        mixer.release(voice);
+        mixer.generate(&mut buffer);
+        assert!(buffer.iter().all(|tone| tone.is_finite()));
+    }
+
+    #[test]
+    fn pitch_changes_are_checked_against_nyquist() {
+        let mut sample = Sample::default();
+        sample.osc_frequency(12_000).osc_cents(1200.0);
+        assert!(matches!(
+            sample.validate(),
+            Err(SampleError::AboveNyquist {
+                parameter: "osc_cents",
+                frequency: 24_000,
+                ..
+            })
+        ));
+        assert_eq!(sample.validate_for(48_000), Ok(()));
+
+        let mut sample = Sample::default();
+        sample.arp_amount(100_000.0).arp_time(0.0);
+        assert!(matches!(
+            sample.validate(),
+            Err(SampleError::AboveNyquist {
+                parameter: "arp_amount",
+                ..
+            })
+        ));
+    }
+}